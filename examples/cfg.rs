@@ -0,0 +1,25 @@
+extern crate reustmann;
+
+use std::fs;
+use std::io;
+
+use reustmann::analysis::ControlFlowGraph;
+use reustmann::{Program, Interpreter};
+
+const ARCH_WIDTH: usize = 8; // word size
+
+fn main() {
+    let file = std::env::args().nth(1).expect("missing instructions file");
+    let mut instructions = fs::read(file).unwrap();
+    if instructions.last() == Some(&b'\n') {
+        instructions.pop();
+    }
+    let program = Program::from_iter(instructions);
+
+    // the memory is as long as the program
+    let mut interpreter = Interpreter::new(program.memory().len(), ARCH_WIDTH).unwrap();
+    interpreter.copy_program(&program);
+
+    let cfg = ControlFlowGraph::new(interpreter.memory());
+    cfg.write_dot(&mut io::stdout()).unwrap();
+}
@@ -0,0 +1,31 @@
+extern crate reustmann;
+
+use std::path::Path;
+use std::process;
+
+use reustmann::corpus;
+
+fn main() {
+    let paths: Vec<_> = std::env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("usage: corpus <program or directory>...");
+        process::exit(2);
+    }
+
+    let mut failed = false;
+    for path in &paths {
+        let path = Path::new(path);
+        let result = if path.is_dir() { corpus::check_dir(path) } else { corpus::check_program(path) };
+        match result {
+            Ok(mismatches) => for mismatch in mismatches {
+                println!("{}", mismatch);
+                failed = true;
+            },
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                process::exit(2);
+            },
+        }
+    }
+    process::exit(if failed { 1 } else { 0 });
+}
@@ -24,9 +24,8 @@ fn main() {
     for _ in 0..CYCLE_LIMIT {
         // each interpreter step return a statement
         // while no `HALT` statement is found, we continue
-        match interpreter.step(&mut input, &mut output) {
-            Statement(op_codes::HALT, _) => break,
-            _ => ()
+        if let Statement(op_codes::HALT, _) = interpreter.step(&mut input, &mut output) {
+            break
         }
     }
 
@@ -0,0 +1,31 @@
+extern crate reustmann;
+
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use reustmann::device::Framebuffer;
+use reustmann::{Program, Interpreter};
+
+const ARCH_LENGTH: usize = 512; // memory length
+const ARCH_WIDTH: usize = 8; // word size
+const SIDE: usize = 16; // framebuffer width and height
+
+fn main() {
+    // push 0, then increment and duplicate the top of the stack forever,
+    // the stack grows down from the end of the memory, over the framebuffer
+    let program = Program::from_iter("0L.D]".bytes());
+
+    let mut interpreter = Interpreter::new(ARCH_LENGTH, ARCH_WIDTH).unwrap();
+    interpreter.copy_program(&program);
+
+    let framebuffer = Rc::new(RefCell::new(Framebuffer::new(SIDE, SIDE)));
+    interpreter.map_device(ARCH_LENGTH - SIDE * SIDE, SIDE * SIDE, framebuffer.clone()).unwrap();
+
+    // one push every three instructions
+    for _ in 0..SIDE * SIDE * 3 {
+        interpreter.step(&mut io::empty(), &mut io::sink());
+    }
+
+    framebuffer.borrow().write_ppm(&mut io::stdout()).unwrap();
+}
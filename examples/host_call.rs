@@ -0,0 +1,51 @@
+extern crate reustmann;
+
+use std::io::{self, Write};
+
+use reustmann::instruction::op_codes;
+use reustmann::{HostContext, Interpreter, Program, Statement};
+
+const ARCH_LENGTH: usize = 100; // memory length
+const ARCH_WIDTH: usize = 16; // word size
+const CYCLE_LIMIT: usize = 200;
+
+// unassigned op codes given a meaning by the host
+const PRINT_NUMBER: u8 = b'n';
+const READ_NUMBER: u8 = b'r';
+
+fn main() {
+    // read a number, square it and print it
+    let program = Program::from_iter("rD*nH".bytes());
+
+    let mut interpreter = Interpreter::new(ARCH_LENGTH, ARCH_WIDTH).unwrap();
+    interpreter.copy_program(&program);
+
+    interpreter.register_host_call(PRINT_NUMBER, |context: &mut HostContext| {
+        let number = context.pop();
+        number.to_string().bytes().all(|b| context.write_word(b as u32).is_ok())
+    }).unwrap();
+
+    interpreter.register_host_call(READ_NUMBER, |context: &mut HostContext| {
+        let mut number = 0u32;
+        while let Ok(Some(word)) = context.read_word() {
+            match (word as u8 as char).to_digit(10) {
+                Some(digit) => number = number.wrapping_mul(10).wrapping_add(digit),
+                None => break,
+            }
+        }
+        context.push(number);
+        context.set_nz(number != 0);
+        true
+    }).unwrap();
+
+    let mut input = io::stdin();
+    let mut output = Vec::new();
+    for _ in 0..CYCLE_LIMIT {
+        if let Statement(op_codes::HALT, _) = interpreter.step(&mut input, &mut output) {
+            break
+        }
+    }
+
+    io::stdout().write_all(&output).unwrap();
+    println!();
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+use crate::dialect::Dialect;
+use crate::instruction::{ControlFlow, Instruction, LongMnemonic, is_valid_op_code, op_code_info};
+use crate::instruction::op_codes::*;
+use crate::memory::{Word, decode};
+
+/// The reason why the control can flow from one instruction to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Execution continues at the next instruction,
+    /// also used when a conditional skip is not taken.
+    Fallthrough,
+    /// A `SKIPn` jumps over the next `n` instructions.
+    Skip,
+    /// A conditional skip (`BZ` ... `BGE`) jumps over the next instruction.
+    ConditionalSkip,
+    /// An `ENDL` resumes after the preceding `LOOP`.
+    LoopBack,
+    /// A `BRAN` or a `BRAP` resumes after the nearest `TARGET`,
+    /// a `BRAN` that sets the SP to the `TARGET` stays on itself.
+    Branch,
+    /// A `RESET` restarts execution at location 0.
+    Reset,
+    /// A `POPPC` resumes at an address popped from the stack,
+    /// the destination is only known at runtime.
+    Indirect,
+}
+
+/// An edge of the control-flow graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Address of the instruction the edge comes from.
+    pub from: usize,
+    /// Address of the next instruction, `None` if it is unknown.
+    pub to: Option<usize>,
+    pub kind: EdgeKind,
+    /// The edge goes forward but crossed location L - 1 and wrapped around to 0.
+    pub wrapped: bool,
+}
+
+impl Edge {
+    fn forward(from: usize, n: usize, len: usize, kind: EdgeKind) -> Edge {
+        Edge { from, to: Some((from + n) % len), kind, wrapped: from + n >= len }
+    }
+
+    fn to(from: usize, to: usize, kind: EdgeKind) -> Edge {
+        Edge { from, to: Some(to), kind, wrapped: false }
+    }
+}
+
+/// The control-flow graph of a memory image, one node per memory location.
+///
+/// The graph is a static view of the memory at the time it was built,
+/// a program that modify itself can take paths that are not represented here.
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph {
+    memory: Vec<Word>,
+    successors: Vec<Vec<Edge>>,
+}
+
+fn search_forward(memory: &[Word], pc: usize, op_code: OpCode, wraps: bool) -> Option<usize> {
+    let matches = |&i: &usize| memory[i] == op_code as Word;
+    let found = (pc + 1..memory.len()).find(matches);
+    if found.is_none() && wraps { (0..pc).find(matches) } else { found }
+}
+
+fn search_backward(memory: &[Word], pc: usize, op_code: OpCode, wraps: bool) -> Option<usize> {
+    let matches = |&i: &usize| memory[i] == op_code as Word;
+    let found = (0..pc).rev().find(matches);
+    if found.is_none() && wraps { (pc + 1..memory.len()).rev().find(matches) } else { found }
+}
+
+fn successors(memory: &[Word], pc: usize, dialect: &Dialect) -> Vec<Edge> {
+    let len = memory.len();
+    let op_code = decode(memory[pc]);
+    let control_flow = op_code_info(op_code).map_or(ControlFlow::Fallthrough, |info| info.control_flow);
+    let found = match op_code {
+        ENDL => search_backward(memory, pc, LOOP, dialect.loop_search_wraps),
+        BRAP => search_backward(memory, pc, TARGET, dialect.target_search_wraps),
+        BRAN => search_forward(memory, pc, TARGET, dialect.target_search_wraps),
+        _ => None,
+    };
+
+    match (control_flow, found) {
+        (ControlFlow::Halt, _) => Vec::new(),
+        (ControlFlow::Reset, _) => vec![Edge::to(pc, 0, EdgeKind::Reset)],
+        (ControlFlow::Indirect, _) => vec![Edge { from: pc, to: None, kind: EdgeKind::Indirect, wrapped: false }],
+        (ControlFlow::ConditionalSkip, _) => vec![
+            Edge::forward(pc, 1, len, EdgeKind::Fallthrough),
+            Edge::forward(pc, 2, len, EdgeKind::ConditionalSkip),
+        ],
+        (ControlFlow::Skip(n), _) => vec![Edge::forward(pc, n + 1, len, EdgeKind::Skip)],
+        (ControlFlow::LoopBack, Some(i)) => vec![Edge::to(pc, (i + 1) % len, EdgeKind::LoopBack)],
+        (ControlFlow::TargetSearch, Some(_)) if op_code == BRAN && dialect.bran_sets_sp => {
+            vec![Edge::to(pc, pc, EdgeKind::Branch)]
+        },
+        (ControlFlow::TargetSearch, Some(i)) if op_code == BRAN => {
+            vec![Edge::forward(pc, (i + len - pc) % len + 1, len, EdgeKind::Branch)]
+        },
+        (ControlFlow::TargetSearch, Some(i)) => vec![Edge::to(pc, (i + 1) % len, EdgeKind::Branch)],
+        _ => vec![Edge::forward(pc, 1, len, EdgeKind::Fallthrough)],
+    }
+}
+
+impl ControlFlowGraph {
+    /// Build the control-flow graph of a memory image,
+    /// the length of the memory is the arch length of the machine.
+    ///
+    /// The edges follow the default dialect, `Dialect::reustmann()`.
+    pub fn new(memory: &[Word]) -> ControlFlowGraph {
+        ControlFlowGraph::with_dialect(memory, &Dialect::default())
+    }
+
+    /// Build the control-flow graph of a memory image
+    /// executed with the semantics of `dialect`.
+    pub fn with_dialect(memory: &[Word], dialect: &Dialect) -> ControlFlowGraph {
+        let successors = (0..memory.len()).map(|pc| successors(memory, pc, dialect)).collect();
+        ControlFlowGraph { memory: memory.to_vec(), successors }
+    }
+
+    /// Return the number of nodes, the arch length of the memory image.
+    pub fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Return `true` if the memory image was empty.
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty()
+    }
+
+    /// Return the op code executed at the given address.
+    pub fn op_code(&self, addr: usize) -> OpCode {
+        decode(self.memory[addr])
+    }
+
+    /// Return the edges leaving the instruction at the given address.
+    pub fn successors(&self, addr: usize) -> &[Edge] {
+        &self.successors[addr]
+    }
+
+    /// Return all the edges of the graph ordered by source address.
+    pub fn edges(&self) -> impl Iterator<Item=&Edge> {
+        self.successors.iter().flat_map(|edges| edges.iter())
+    }
+
+    /// Mark the addresses reachable from `entry` by following the known edges.
+    ///
+    /// Indirect edges can't be followed,
+    /// the addresses only reachable through them are not marked.
+    pub fn reachable(&self, entry: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        if entry >= self.len() {
+            return reachable
+        }
+
+        let mut queue = VecDeque::new();
+        reachable[entry] = true;
+        queue.push_back(entry);
+        while let Some(addr) = queue.pop_front() {
+            for edge in self.successors(addr) {
+                if let Some(to) = edge.to {
+                    if !reachable[to] {
+                        reachable[to] = true;
+                        queue.push_back(to);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Write the graph in the Graphviz DOT format,
+    /// nodes not reachable from location 0 are greyed out.
+    pub fn write_dot<W: ?Sized + Write>(&self, output: &mut W) -> io::Result<()> {
+        let reachable = self.reachable(0);
+
+        writeln!(output, "digraph reustmann {{")?;
+        writeln!(output, "    node [shape=box, fontname=\"monospace\"];")?;
+        for (addr, &word) in self.memory.iter().enumerate() {
+            let op_code = decode(word);
+            let name: LongMnemonic = Into::<Instruction>::into(op_code).into();
+            let label = if is_valid_op_code(op_code) && word == op_code as Word {
+                format!("{:#06x}: {}", addr, name)
+            } else {
+                format!("{:#06x}: {} ({:#04x})", addr, name, word)
+            };
+            let style = if reachable[addr] { "" } else { ", color=grey, fontcolor=grey" };
+            writeln!(output, "    n{} [label=\"{}\"{}];", addr, label, style)?;
+        }
+
+        if self.edges().any(|edge| edge.to.is_none()) {
+            writeln!(output, "    unknown [label=\"?\", shape=circle];")?;
+        }
+
+        for edge in self.edges() {
+            let to = match edge.to {
+                Some(to) => format!("n{}", to),
+                None => "unknown".to_string(),
+            };
+            let attrs = match edge.kind {
+                EdgeKind::Fallthrough => "",
+                EdgeKind::Skip => " [label=\"skip\"]",
+                EdgeKind::ConditionalSkip => " [label=\"taken\", style=dashed]",
+                EdgeKind::LoopBack => " [label=\"loop\", color=blue]",
+                EdgeKind::Branch => " [label=\"branch\", color=blue]",
+                EdgeKind::Reset => " [label=\"reset\", color=red]",
+                EdgeKind::Indirect => " [label=\"pop pc\", style=dotted, color=red]",
+            };
+            writeln!(output, "    n{} -> {}{};", edge.from, to, attrs)?;
+        }
+        writeln!(output, "}}")
+    }
+
+    /// Return the graph in the Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Vec::new();
+        self.write_dot(&mut dot).expect("writing into a Vec can't fail");
+        String::from_utf8(dot).expect("DOT output is valid UTF-8")
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+use crate::analysis::cfg::{ControlFlowGraph, EdgeKind};
+use crate::instruction::{Instruction, LongMnemonic, is_valid_mnemonic, is_valid_op_code};
+use crate::instruction::op_codes::*;
+use crate::interpreter::Interpreter;
+use crate::program::Program;
+
+/// A structural problem found in a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// An `ENDL` without a preceding `LOOP`, executed as a NOP.
+    EndlWithoutLoop,
+    /// A `BRAN` without a following `TARGET`, executed as a NOP.
+    BranWithoutTarget,
+    /// A `BRAP` without a preceding `TARGET`, executed as a NOP.
+    BrapWithoutTarget,
+    /// A skip that jumps past location L - 1 and wraps around to the start of memory.
+    SkipWraps,
+    /// A run of `len` instructions that can never be executed.
+    Unreachable { len: usize },
+    /// A `HALT` that can never be executed.
+    UnreachableHalt,
+    /// A char that is not a mnemonic and will be executed as a NOP.
+    DataAsNop { byte: u8 },
+    /// A char that is not a mnemonic but that has the value of an op code
+    /// once truncated to the arch width.
+    DataAsOpCode { byte: u8, op_code: OpCode },
+    /// The program is longer than the memory, `len` chars are not copied.
+    Truncated { len: usize },
+}
+
+impl LintKind {
+    /// A stable identifier for this kind of lint.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            LintKind::EndlWithoutLoop => "endl-without-loop",
+            LintKind::BranWithoutTarget => "bran-without-target",
+            LintKind::BrapWithoutTarget => "brap-without-target",
+            LintKind::SkipWraps => "skip-wraps",
+            LintKind::Unreachable { .. } => "unreachable",
+            LintKind::UnreachableHalt => "unreachable-halt",
+            LintKind::DataAsNop { .. } => "data-as-nop",
+            LintKind::DataAsOpCode { .. } => "data-as-op-code",
+            LintKind::Truncated { .. } => "truncated",
+        }
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LintKind::EndlWithoutLoop => f.write_str("ENDL has no preceding LOOP"),
+            LintKind::BranWithoutTarget => f.write_str("BRAN has no following TARGET"),
+            LintKind::BrapWithoutTarget => f.write_str("BRAP has no preceding TARGET"),
+            LintKind::SkipWraps => f.write_str("skip wraps around the end of memory"),
+            LintKind::Unreachable { len } => write!(f, "{} unreachable instruction(s)", len),
+            LintKind::UnreachableHalt => f.write_str("HALT can never be reached"),
+            LintKind::DataAsNop { byte } => {
+                write!(f, "{:#04x} is not a mnemonic and will be executed as NOP", byte)
+            },
+            LintKind::DataAsOpCode { byte, op_code } => {
+                let name: LongMnemonic = Into::<Instruction>::into(op_code).into();
+                write!(f, "{:#04x} is not a mnemonic but will be executed as {}", byte, name)
+            },
+            LintKind::Truncated { len } => write!(f, "{} char(s) don't fit in memory", len),
+        }
+    }
+}
+
+/// A lint found at a memory location.
+///
+/// The `Display` implementation gives a machine-readable line:
+/// `address: code: message`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lint {
+    pub addr: usize,
+    pub kind: LintKind,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.addr, self.kind.code(), self.kind)
+    }
+}
+
+/// Lint a program as if copied in the memory of a machine of rank
+/// `arch_length` and `arch_width`, the lints are sorted by address.
+///
+/// The chars are truncated to the arch width like the interpreter does,
+/// at W = 6 the char `e` (0x65) is executed as 0x25, a `SKIP1`.
+///
+/// Reachability is computed from location 0, if a reachable `POPPC` is found
+/// every location could be reached and no unreachable code is reported.
+/// Chars that are not mnemonics are only reported at reachable locations.
+///
+/// # Panics
+///
+/// If `arch_width` is not in the range accepted by `Interpreter::new`.
+pub fn lint(program: &Program, arch_length: usize, arch_width: usize) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    let source = program.memory();
+    let len = source.len().min(arch_length);
+
+    if source.len() > arch_length {
+        lints.push(Lint { addr: arch_length, kind: LintKind::Truncated { len: source.len() - arch_length } });
+    }
+
+    if arch_length == 0 {
+        return lints
+    }
+
+    let mut interpreter = Interpreter::new(arch_length, arch_width).expect("invalid machine rank");
+    interpreter.copy_program(program);
+    let cfg = ControlFlowGraph::new(interpreter.memory());
+
+    for addr in 0..len {
+        let op_code = cfg.op_code(addr);
+        let edges = cfg.successors(addr);
+        let fallthrough = edges.iter().all(|e| e.kind == EdgeKind::Fallthrough);
+        let kind = match op_code {
+            ENDL if fallthrough => Some(LintKind::EndlWithoutLoop),
+            BRAN if fallthrough => Some(LintKind::BranWithoutTarget),
+            BRAP if fallthrough => Some(LintKind::BrapWithoutTarget),
+            _ => {
+                let wraps = edges.iter().any(|e| {
+                    e.wrapped && (e.kind == EdgeKind::Skip || e.kind == EdgeKind::ConditionalSkip)
+                });
+                if wraps { Some(LintKind::SkipWraps) } else { None }
+            },
+        };
+        if let Some(kind) = kind {
+            lints.push(Lint { addr, kind });
+        }
+    }
+
+    let reachable = cfg.reachable(0);
+    let indirect = cfg.edges().any(|e| e.kind == EdgeKind::Indirect && reachable[e.from]);
+    let is_reachable = |addr: usize| indirect || reachable[addr];
+
+    let mut addr = 0;
+    while addr < len {
+        if is_reachable(addr) {
+            addr += 1;
+            continue
+        }
+        let start = addr;
+        while addr < len && !is_reachable(addr) {
+            if cfg.op_code(addr) == HALT {
+                lints.push(Lint { addr, kind: LintKind::UnreachableHalt });
+            }
+            addr += 1;
+        }
+        lints.push(Lint { addr: start, kind: LintKind::Unreachable { len: addr - start } });
+    }
+
+    // unreachable data is already reported as a whole by the unreachable lint
+    for (addr, &byte) in source.iter().enumerate().take(len) {
+        if is_reachable(addr) && !is_valid_mnemonic(byte as char) {
+            let op_code = cfg.op_code(addr);
+            let kind = if op_code != NOP && is_valid_op_code(op_code) {
+                LintKind::DataAsOpCode { byte, op_code }
+            } else {
+                LintKind::DataAsNop { byte }
+            };
+            lints.push(Lint { addr, kind });
+        }
+    }
+
+    lints.sort_by_key(|lint| lint.addr);
+    lints
+}
@@ -0,0 +1,11 @@
+//! Static analysis of Reustmann memory images.
+//!
+//! The analysis works on the op codes as stored in the interpreter memory,
+//! use [`Interpreter::memory`](../struct.Interpreter.html#method.memory) to get them
+//! once a program has been copied.
+
+pub mod cfg;
+pub mod lint;
+
+pub use self::cfg::{ControlFlowGraph, Edge, EdgeKind};
+pub use self::lint::{lint, Lint, LintKind};
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::ops::Range;
+use std::slice;
+
+use reustmann::StepDetails;
+use reustmann::instruction::{Instruction, LongMnemonic, Mnemonic, OpCode, is_valid_op_code};
+use reustmann::memory::decode;
+
+use expr::{Context, Expr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Stop before the instruction at this address is executed.
+    Address(usize),
+    /// Stop before an instruction with this op code is executed.
+    OpCode(OpCode),
+    /// Stop before any instruction when the expression is true.
+    Condition(Expr),
+    /// Stop after an `OUT` wrote a word.
+    Output,
+    /// Stop after an `IN` read a word.
+    Input,
+    /// Stop after the word at this address changed.
+    WatchMemory(usize),
+    /// Stop after the SP changed.
+    WatchSp,
+    /// Stop after the NZ flag changed.
+    WatchNz,
+    /// Stop after the PC entered this range of addresses.
+    WatchPcIn(Range<usize>),
+    /// Stop after the value of the expression changed.
+    WatchExpr(Expr),
+}
+
+fn is_true(expr: &Expr, context: &Context) -> Result<bool, String> {
+    expr.eval(context).map(|value| value != 0)
+}
+
+impl Breakpoint {
+    pub fn is_watchpoint(&self) -> bool {
+        matches!(*self, Breakpoint::WatchMemory(_) | Breakpoint::WatchSp | Breakpoint::WatchNz |
+                        Breakpoint::WatchPcIn(_) | Breakpoint::WatchExpr(_))
+    }
+
+    pub fn kind(&self) -> &'static str {
+        if self.is_watchpoint() { "Watchpoint" } else { "Breakpoint" }
+    }
+
+    /// Is the breakpoint hit by the instruction about to be executed.
+    pub fn hit_before(&self, context: &Context) -> Result<bool, String> {
+        let pc = context.interpreter.pc();
+        match *self {
+            Breakpoint::Address(addr) => Ok(addr == pc),
+            Breakpoint::OpCode(op_code) => Ok(decode(context.interpreter.memory()[pc]) == op_code),
+            Breakpoint::Condition(ref expr) => is_true(expr, context),
+            _ => Ok(false),
+        }
+    }
+
+    /// The value looked at by a watchpoint,
+    /// `None` for the other breakpoints or when it can not be evaluated.
+    pub fn watched(&self, context: &Context) -> Option<i64> {
+        let interpreter = context.interpreter;
+        match *self {
+            Breakpoint::WatchMemory(addr) => interpreter.memory().get(addr).map(|&word| word as i64),
+            Breakpoint::WatchSp => Some(interpreter.sp() as i64),
+            Breakpoint::WatchNz => Some(interpreter.nz() as i64),
+            Breakpoint::WatchPcIn(_) => Some(interpreter.pc() as i64),
+            Breakpoint::WatchExpr(ref expr) => expr.eval(context).ok(),
+            _ => None,
+        }
+    }
+
+    /// Is the breakpoint hit by the instruction just executed,
+    /// `old` and `new` are the watched values before and after it.
+    pub fn hit_after(&self, details: &StepDetails, old: Option<i64>, new: Option<i64>) -> bool {
+        match *self {
+            Breakpoint::Output => details.output.is_some(),
+            Breakpoint::Input => details.input.is_some(),
+            Breakpoint::WatchPcIn(ref range) => match (old, new) {
+                (Some(old), Some(new)) => !range.contains(&(old as usize)) && range.contains(&(new as usize)),
+                _ => false,
+            },
+            _ => self.is_watchpoint() && old != new,
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Breakpoint::Address(addr) => write!(f, "address {:#06x}", addr),
+            Breakpoint::OpCode(op_code) if is_valid_op_code(op_code) => {
+                let instr: Instruction = op_code.into();
+                let mnemo: Mnemonic = instr.into();
+                let long_mnemo: LongMnemonic = instr.into();
+                write!(f, "op code '{}' ({})", mnemo, long_mnemo)
+            },
+            Breakpoint::OpCode(op_code) => write!(f, "op code {:#04x}", op_code),
+            Breakpoint::Condition(ref expr) => write!(f, "`{}`", expr),
+            Breakpoint::Output => f.write_str("output"),
+            Breakpoint::Input => f.write_str("input"),
+            Breakpoint::WatchMemory(addr) => write!(f, "address {:#06x}", addr),
+            Breakpoint::WatchSp => f.write_str("sp"),
+            Breakpoint::WatchNz => f.write_str("nz"),
+            Breakpoint::WatchPcIn(ref range) => write!(f, "pc in {:#06x}..{:#06x}", range.start, range.end),
+            Breakpoint::WatchExpr(ref expr) => write!(f, "`{}`", expr),
+        }
+    }
+}
+
+/// A breakpoint with the condition it is checked under.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: usize,
+    pub breakpoint: Breakpoint,
+    pub condition: Option<Expr>,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} on {}", self.breakpoint.kind(), self.id, self.breakpoint)?;
+        if let Some(ref condition) = self.condition {
+            write!(f, " if `{}`", condition)?;
+        }
+        Ok(())
+    }
+}
+
+/// A breakpoint hit during the execution.
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub entry: Entry,
+    /// The old and the new value of a watchpoint.
+    pub change: Option<(i64, i64)>,
+    /// The expression that could not be evaluated, stopping the execution.
+    pub error: Option<String>,
+}
+
+impl fmt::Display for Hit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.entry)?;
+        if let Some((old, new)) = self.change {
+            write!(f, ": {} -> {}", old, new)?;
+        }
+        if let Some(ref error) = self.error {
+            write!(f, " ({})", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Entry {
+    /// The command creating the breakpoint again.
+    pub fn command(&self) -> String {
+        let mut command = match self.breakpoint {
+            Breakpoint::Address(addr) => format!("break {:#06x}", addr),
+            Breakpoint::OpCode(op_code) => {
+                let mnemo: Mnemonic = Into::<Instruction>::into(op_code).into();
+                format!("break op {}", mnemo)
+            },
+            Breakpoint::Condition(ref expr) => format!("break if {}", expr),
+            Breakpoint::Output => "break on-output".to_string(),
+            Breakpoint::Input => "break on-input".to_string(),
+            Breakpoint::WatchMemory(addr) => format!("watch {:#06x}", addr),
+            Breakpoint::WatchSp => "watch sp".to_string(),
+            Breakpoint::WatchNz => "watch nz".to_string(),
+            Breakpoint::WatchPcIn(ref range) => format!("watch pc in {:#06x}..{:#06x}", range.start, range.end),
+            Breakpoint::WatchExpr(ref expr) => format!("watch {}", expr),
+        };
+        if let Some(ref condition) = self.condition {
+            command.push_str(&format!(" if {}", condition));
+        }
+        command
+    }
+
+    // a triggered breakpoint is hit if its condition is true
+    fn hit(&self, triggered: Result<bool, String>, change: Option<(i64, i64)>, context: &Context) -> Option<Hit> {
+        let triggered = triggered.and_then(|triggered| match self.condition {
+            Some(ref condition) if triggered => is_true(condition, context),
+            _ => Ok(triggered),
+        });
+        match triggered {
+            Ok(false) => None,
+            Ok(true) => Some(Hit { entry: self.clone(), change, error: None }),
+            Err(error) => Some(Hit { entry: self.clone(), change, error: Some(error) }),
+        }
+    }
+}
+
+/// The breakpoints and watchpoints of the debugger,
+/// numbered together from 1 in creation order.
+#[derive(Debug, Default)]
+pub struct Breakpoints {
+    last_id: usize,
+    entries: Vec<Entry>,
+}
+
+impl Breakpoints {
+    /// Add a breakpoint, numbered after the last one.
+    pub fn insert(&mut self, breakpoint: Breakpoint, condition: Option<Expr>) -> &Entry {
+        self.last_id += 1;
+        self.entries.push(Entry { id: self.last_id, breakpoint, condition });
+        self.entries.last().unwrap()
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Entry> {
+        let index = self.entries.iter().position(|entry| entry.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Entry> {
+        self.entries.iter()
+    }
+
+    /// Return the first breakpoint hit by the instruction about to be executed.
+    pub fn hit_before(&self, context: &Context) -> Option<Hit> {
+        self.iter().find_map(|entry| entry.hit(entry.breakpoint.hit_before(context), None, context))
+    }
+
+    /// Return the values looked at by the watchpoints,
+    /// to give to `hit_after` once the instruction is executed.
+    pub fn watched(&self, context: &Context) -> Vec<Option<i64>> {
+        self.iter().map(|entry| entry.breakpoint.watched(context)).collect()
+    }
+
+    /// Return the first breakpoint hit by the instruction just executed.
+    pub fn hit_after(&self, details: &StepDetails, old: &[Option<i64>], context: &Context) -> Option<Hit> {
+        let new = self.watched(context);
+        self.hit_by_step(details, old, &new, context)
+    }
+
+    /// Return the first breakpoint hit by the instruction just undone,
+    /// `new` are the watched values before it was undone.
+    pub fn hit_undone(&self, details: &StepDetails, new: &[Option<i64>], context: &Context) -> Option<Hit> {
+        let old = self.watched(context);
+        self.hit_by_step(details, &old, new, context)
+    }
+
+    // the conditions are checked in `context`
+    fn hit_by_step(&self, details: &StepDetails, old: &[Option<i64>], new: &[Option<i64>], context: &Context) -> Option<Hit> {
+        self.iter().zip(old.iter().zip(new)).find_map(|(entry, (&old, &new))| {
+            let triggered = entry.breakpoint.hit_after(details, old, new);
+            let change = old.and_then(|old| new.map(|new| (old, new)));
+            entry.hit(Ok(triggered), change, context)
+        })
+    }
+}
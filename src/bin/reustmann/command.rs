@@ -1,6 +1,34 @@
 use std::borrow::Cow;
+use std::ops::Range;
 use std::str::{self, FromStr};
 
+use reustmann::{Dialect, Encoding, Program, Word};
+use reustmann::corpus;
+use reustmann::instruction::{INSTRUCTIONS, OpCode, is_valid_mnemonic, mnemonic_info};
+
+use breakpoint::Breakpoint;
+use expr::Expr;
+
+/// The number of words dumped by `x` without a count.
+pub const DEFAULT_EXAMINE_COUNT: usize = 16;
+
+/// The maximum number of cycles executed by `continue` without a limit.
+pub const DEFAULT_CONTINUE_LIMIT: usize = 1_000_000;
+
+/// Where the bytes given to the programs come from.
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    Bytes(Vec<u8>),
+    File(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    Pc,
+    Sp,
+    Nz,
+}
+
 #[derive(Debug, Clone)]
 pub enum Command {
     UnsetInterpreter,
@@ -13,15 +41,130 @@ pub enum Command {
     Copy(String, bool),
     Reset,
     Step(usize),
+    Continue(usize),
+    /// Undo a number of steps, stop on a breakpoint.
+    ReverseStep(usize),
+    /// Undo steps until a breakpoint, at most a number of them.
+    ReverseContinue(usize),
+    Break(Breakpoint, Option<Expr>),
+    Delete(Option<usize>),
+    InfoBreakpoints,
+    Print(Expr),
+    /// Fail when the expression evaluates to zero.
+    Assert(Expr),
+    Input(Option<InputSource>),
+    AppendInput(InputSource),
+    Set(Register, Expr),
+    /// Write words in memory from an address.
+    Poke(usize, Vec<Word>),
+    /// Disassemble from an address, the PC by default.
+    Disas(Option<usize>, Option<usize>),
+    /// Dump a number of words from an address.
+    Examine(usize, usize),
+    InfoStack,
+    PcLines(usize),
+    SpLines(usize),
+    Encoding(Option<Encoding>),
+    Dialect(Option<Dialect>),
+    SaveSession(String),
+    LoadSession(String),
+    /// Start appending the commands executed to a file, stop with `None`.
+    Record(Option<String>),
+    Replay(String),
     Repeat,
     Exit,
 }
 
+/// Parse a decimal or a `0x` prefixed hexadecimal number.
+fn parse_number(s: &str) -> Result<usize, Cow<'static, str>> {
+    let result = match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    result.map_err(|e| format!("invalid number {:?}: {}", s, e).into())
+}
+
+/// Parse a `start..end` or a `start..=end` range of addresses.
+fn parse_range(s: &str) -> Result<Range<usize>, Cow<'static, str>> {
+    let dots = s.find("..").ok_or_else(|| format!("invalid range {:?}", s))?;
+    let start = parse_number(&s[..dots])?;
+    let end = match s[dots + 2..].strip_prefix('=') {
+        Some(end) => parse_number(end)? + 1,
+        None => parse_number(&s[dots + 2..])?,
+    };
+    if start >= end {
+        return Err(format!("empty range {:?}", s).into())
+    }
+    Ok(start..end)
+}
+
+/// Parse a quoted string with escapes, `hex <bytes>` or `file <path>`.
+fn parse_input_source(s: &str) -> Result<InputSource, Cow<'static, str>> {
+    if let Some(hex) = s.strip_prefix("hex ") {
+        let digits: Vec<char> = hex.chars().filter(|c| !c.is_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err("odd number of hex digits".into())
+        }
+        let bytes = digits.chunks(2).map(|pair| {
+            let pair: String = pair.iter().collect();
+            u8::from_str_radix(&pair, 16).map_err(|_| format!("invalid hex byte {:?}", pair))
+        });
+        Ok(InputSource::Bytes(bytes.collect::<Result<_, _>>()?))
+    } else if let Some(path) = s.strip_prefix("file ") {
+        Ok(InputSource::File(path.trim().to_string()))
+    } else {
+        Ok(InputSource::Bytes(corpus::unquote(s)?))
+    }
+}
+
+/// Split the address starting a command from the rest of it.
+fn split_address(s: &str) -> Result<(usize, &str), Cow<'static, str>> {
+    match s.split_once(char::is_whitespace) {
+        Some((addr, rest)) if !rest.trim().is_empty() => Ok((parse_number(addr)?, rest.trim())),
+        _ => Err("missing address or value".into()),
+    }
+}
+
+/// Parse a quoted string of raw bytes, a number or a mnemonic,
+/// the digits are numbers rather than the `SKIP` mnemonics.
+fn parse_words(s: &str) -> Result<Vec<Word>, Cow<'static, str>> {
+    if s.starts_with('"') {
+        Ok(corpus::unquote(s)?.into_iter().map(Word::from).collect())
+    } else if let Ok(value) = parse_number(s) {
+        Ok(vec![value as Word])
+    } else {
+        parse_op_code(s).map(|op_code| vec![op_code as Word])
+    }
+}
+
+/// Split a breakpoint from its `if <condition>` suffix.
+fn split_condition(s: &str) -> Result<(&str, Option<Expr>), Cow<'static, str>> {
+    let (spec, condition) = match s.strip_prefix("if ") {
+        Some(condition) => ("", condition),
+        None => match s.find(" if ") {
+            Some(i) => (&s[..i], &s[i + " if ".len()..]),
+            None => return Ok((s, None)),
+        },
+    };
+    Ok((spec.trim(), Some(condition.parse()?)))
+}
+
+/// Parse a short mnemonic, like `H`, or a long one, like `Halt`.
+fn parse_op_code(s: &str) -> Result<OpCode, Cow<'static, str>> {
+    let mut chars = s.chars();
+    let info = match (chars.next(), chars.next()) {
+        (Some(c), None) => mnemonic_info(c),
+        _ => INSTRUCTIONS.iter().find(|info| info.long_mnemonic.eq_ignore_ascii_case(s)),
+    };
+    info.map(|info| info.op_code).ok_or_else(|| format!("invalid mnemonic {:?}", s).into())
+}
+
 impl FromStr for Command {
     type Err = Cow<'static, str>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut iter = s.split_whitespace();
+        let rest = s.trim().split_once(char::is_whitespace).map_or("", |(_, rest)| rest.trim());
         match iter.next() {
             Some("unset_interpreter") => Ok(Command::UnsetInterpreter),
             Some("interpreter") => {
@@ -58,6 +201,143 @@ impl FromStr for Command {
                 };
                 Ok(Command::Step(count))
             },
+            Some("continue") | Some("c") => {
+                let limit = match iter.next() {
+                    Some(s) => s.parse::<usize>().map_err(|e| e.to_string())?,
+                    None => DEFAULT_CONTINUE_LIMIT,
+                };
+                Ok(Command::Continue(limit))
+            },
+            Some("rstep") | Some("rs") => {
+                let count = match iter.next() {
+                    Some(s) => s.parse::<usize>().map_err(|e| e.to_string())?,
+                    None => 1,
+                };
+                Ok(Command::ReverseStep(count))
+            },
+            Some("rcontinue") | Some("rc") => {
+                let limit = match iter.next() {
+                    Some(s) => s.parse::<usize>().map_err(|e| e.to_string())?,
+                    None => DEFAULT_CONTINUE_LIMIT,
+                };
+                Ok(Command::ReverseContinue(limit))
+            },
+            Some("break") | Some("b") => {
+                let (spec, condition) = split_condition(rest)?;
+                let mut words = spec.split_whitespace();
+                let breakpoint = match (words.next(), condition) {
+                    (None, Some(condition)) => return Ok(Command::Break(Breakpoint::Condition(condition), None)),
+                    (None, None) => return Err("missing breakpoint address".into()),
+                    (Some("op"), condition) => {
+                        let mnemonic = words.next().ok_or("missing mnemonic")?;
+                        (Breakpoint::OpCode(parse_op_code(mnemonic)?), condition)
+                    },
+                    (Some("on-output"), condition) => (Breakpoint::Output, condition),
+                    (Some("on-input"), condition) => (Breakpoint::Input, condition),
+                    (Some(addr), condition) => (Breakpoint::Address(parse_number(addr)?), condition),
+                };
+                if let Some(word) = words.next() {
+                    return Err(format!("unexpected {:?}", word).into())
+                }
+                Ok(Command::Break(breakpoint.0, breakpoint.1))
+            },
+            Some("watch") | Some("w") => {
+                let (spec, condition) = split_condition(rest)?;
+                let watchpoint = match spec {
+                    "" => return Err("missing watched address, register or expression".into()),
+                    "sp" => Breakpoint::WatchSp,
+                    "nz" => Breakpoint::WatchNz,
+                    _ if spec.starts_with("pc in ") => Breakpoint::WatchPcIn(parse_range(spec["pc in ".len()..].trim())?),
+                    _ => match parse_number(spec) {
+                        Ok(addr) => Breakpoint::WatchMemory(addr),
+                        Err(_) => Breakpoint::WatchExpr(spec.parse()?),
+                    },
+                };
+                Ok(Command::Break(watchpoint, condition))
+            },
+            Some("print") | Some("p") => Ok(Command::Print(rest.parse()?)),
+            Some("assert") => Ok(Command::Assert(rest.parse()?)),
+            Some("delete") | Some("d") => {
+                match iter.next() {
+                    Some(s) => Ok(Command::Delete(Some(parse_number(s)?))),
+                    None => Ok(Command::Delete(None)),
+                }
+            },
+            Some("info") => {
+                match iter.next() {
+                    Some("breakpoints") | Some("break") | Some("b") | Some("watchpoints") => Ok(Command::InfoBreakpoints),
+                    Some("stack") => Ok(Command::InfoStack),
+                    Some(s) => Err(format!("invalid info command {:?}", s).into()),
+                    None => Err("missing info command".into()),
+                }
+            },
+            Some("set") => {
+                let register = match iter.next() {
+                    Some("pc_lines") => return Ok(Command::PcLines(parse_number(iter.next().ok_or("missing number of lines")?)?)),
+                    Some("sp_lines") => return Ok(Command::SpLines(parse_number(iter.next().ok_or("missing number of lines")?)?)),
+                    Some("pc") => Register::Pc,
+                    Some("sp") => Register::Sp,
+                    Some("nz") => Register::Nz,
+                    Some(s) => return Err(format!("invalid register {:?}", s).into()),
+                    None => return Err("missing register".into()),
+                };
+                let value = rest.split_once(char::is_whitespace).map_or("", |(_, value)| value);
+                Ok(Command::Set(register, value.parse()?))
+            },
+            Some("poke") => {
+                let (addr, value) = split_address(rest)?;
+                Ok(Command::Poke(addr, parse_words(value)?))
+            },
+            Some("asm") => {
+                let (addr, mnemonics) = split_address(rest)?;
+                let mnemonics: String = mnemonics.chars().filter(|c| !c.is_whitespace()).collect();
+                if let Some(c) = mnemonics.chars().find(|&c| !is_valid_mnemonic(c)) {
+                    return Err(format!("invalid mnemonic {:?}", c).into())
+                }
+                let op_codes = Program::from_iter(mnemonics.bytes()).op_codes();
+                Ok(Command::Poke(addr, op_codes.iter().map(|&op_code| op_code as Word).collect()))
+            },
+            Some("disas") => {
+                let start = iter.next().map(parse_number).transpose()?;
+                let count = iter.next().map(parse_number).transpose()?;
+                Ok(Command::Disas(start, count))
+            },
+            Some(x) if x == "x" || x.starts_with("x/") => {
+                let count = match x.strip_prefix("x/") {
+                    Some(count) => parse_number(count)?,
+                    None => DEFAULT_EXAMINE_COUNT,
+                };
+                let addr = parse_number(iter.next().ok_or("missing address")?)?;
+                Ok(Command::Examine(addr, count))
+            },
+            Some("input") => {
+                match rest.strip_prefix("append ") {
+                    Some(source) => Ok(Command::AppendInput(parse_input_source(source.trim())?)),
+                    None if rest.is_empty() => Ok(Command::Input(None)),
+                    None => Ok(Command::Input(Some(parse_input_source(rest)?))),
+                }
+            },
+            Some("encoding") => {
+                match iter.next() {
+                    Some(s) => Ok(Command::Encoding(Some(s.parse()?))),
+                    None => Ok(Command::Encoding(None)),
+                }
+            },
+            Some("dialect") => {
+                match iter.next() {
+                    Some(s) => Ok(Command::Dialect(Some(s.parse()?))),
+                    None => Ok(Command::Dialect(None)),
+                }
+            },
+            Some("save-session") if !rest.is_empty() => Ok(Command::SaveSession(rest.to_string())),
+            Some("load-session") if !rest.is_empty() => Ok(Command::LoadSession(rest.to_string())),
+            Some("record") => match rest {
+                "" => Err("missing file name".into()),
+                "stop" => Ok(Command::Record(None)),
+                file_name => Ok(Command::Record(Some(file_name.to_string()))),
+            },
+            Some("replay") if !rest.is_empty() => Ok(Command::Replay(rest.to_string())),
+            Some("save-session") | Some("load-session") | Some("replay") => Err("missing file name".into()),
             Some("repeat") | None => Ok(Command::Repeat),
             Some("exit") | Some("quit") | Some("q") => Ok(Command::Exit),
             Some(command) => Err(format!("invalid command {:?}", command).into()),
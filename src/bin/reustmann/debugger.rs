@@ -1,18 +1,25 @@
-use std::fmt::Debug;
-use std::fs;
-use std::io::{self, Read, Write};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
 use std::path::Path;
 
-use reustmann::{Interpreter, DebugInfos, Program, Statement};
+use reustmann::{Interpreter, DebugInfos, Dialect, Encoding, MemoryEvent, Observer, Program, Statement, StepDetails, Word};
+use reustmann::corpus;
 use reustmann::instruction::op_codes;
 
+use breakpoint::{Breakpoints, Entry, Hit};
+use expr::Context;
 use debugger_error::DebuggerError;
-use command::Command;
+use command::{Command, InputSource, Register};
 use display;
-use sink_debug::DebugWrite;
+use session::Session;
 
 const DEFAULT_ARCH_WIDTH: usize = 8;
 
+/// The number of steps that can be undone.
+const HISTORY_LENGTH: usize = 100_000;
+
 fn create_program_from_file<P: AsRef<Path>>(path: &P, ignore_nl: bool) -> io::Result<Program> {
     let mut instructions = fs::read(path)?;
     if ignore_nl && instructions.last() == Some(&b'\n') {
@@ -21,21 +28,69 @@ fn create_program_from_file<P: AsRef<Path>>(path: &P, ignore_nl: bool) -> io::Re
     Ok(Program::from_iter(instructions))
 }
 
-fn display_debugger_error(dbg_err: &DebuggerError) {
-    match *dbg_err {
-        DebuggerError::NoInterpreter => {
-            printlnc!(red: "{}", dbg_err.description());
-            printlnc!(yellow: "{}", "`interpreter [arch_length] [arch_width]` to create one")
-        },
-        DebuggerError::InterpreterCreation(_) => printlnc!(red: "{}", dbg_err.description()),
+/// Why the execution stopped before the requested number of steps.
+#[derive(Debug, Clone)]
+enum Stop {
+    Halt,
+    Breakpoint(Hit),
+}
+
+/// What is needed to undo a step.
+struct Undo {
+    details: StepDetails,
+    nz: bool,
+    /// The words overwritten, in writing order.
+    overwritten: Vec<(usize, Word)>,
+    input_position: usize,
+    output_bytes: usize,
+    output_length: usize,
+    statement: Option<Statement>,
+}
+
+// collects the words overwritten by an instruction
+#[derive(Default)]
+struct Overwritten(Vec<(usize, Word)>);
+
+impl Observer for Overwritten {
+    fn on_memory_write(&mut self, addr: usize, old: Word, _new: Word) {
+        self.0.push((addr, old));
+    }
+}
+
+/// What to do once a line is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+fn read_input_source(source: &InputSource) -> io::Result<Vec<u8>> {
+    match *source {
+        InputSource::Bytes(ref bytes) => Ok(bytes.clone()),
+        InputSource::File(ref path) => fs::read(path),
     }
 }
 
 pub struct Debugger {
     interpreter: Option<Interpreter>,
     number_of_cycles: usize,
+    /// The bytes written by the programs and the number of words they make.
+    output: Vec<u8>,
+    output_length: usize,
+    /// The bytes given to the programs and how many were read.
+    input: Vec<u8>,
+    input_position: usize,
     program_name: Option<String>,
     statement: Option<Statement>,
+    encoding: Encoding,
+    dialect: Dialect,
+    breakpoints: Breakpoints,
+    /// The last steps executed, the most recent at the back.
+    history: VecDeque<Undo>,
+    /// The last command executed, with its line, to repeat it.
+    last_command: Option<(Command, String)>,
+    /// The file the commands executed are appended to.
+    recording: Option<File>,
     pc_lines: usize,
     sp_lines: usize
 }
@@ -51,51 +106,61 @@ impl Debugger {
         Debugger {
             interpreter: None,
             number_of_cycles: 0,
+            output: Vec::new(),
+            output_length: 0,
+            input: Vec::new(),
+            input_position: 0,
             program_name: None,
             statement: None,
-            pc_lines: 10, // FIXME pc_lines need to be always >= sp_lines
+            encoding: Encoding::default(),
+            dialect: Dialect::default(),
+            breakpoints: Breakpoints::default(),
+            history: VecDeque::new(),
+            last_command: None,
+            recording: None,
+            pc_lines: 10,
             sp_lines: 5
         }
     }
 
-    pub fn execute<R: ?Sized + Read, W: ?Sized + DebugWrite>(&mut self, command: &Command, input: &mut R, output: &mut W) /*-> Result<x, y>*/ {
+    pub fn execute(&mut self, command: &Command) -> Result<(), DebuggerError> {
         match *command {
             Command::UnsetInterpreter => {
                 match self.unset_interpreter() {
                     Ok(_) => printlnc!(yellow: "Interpreter correctly unset."),
-                    Err(err) => display_debugger_error(&err),
+                    Err(err) => return Err(err),
                 }
             }
             Command::InfosInterpreter => {
                 match self.interpreter() {
                     Ok(interpreter) => display::display_interpreter_properties(interpreter),
-                    Err(err) => display_debugger_error(&err),
+                    Err(err) => return Err(err),
                 }
             },
             Command::SetInterpreter{ arch_length, arch_width } => {
                 match self.set_interpreter(arch_length, arch_width) {
                     Ok(_) => {
                         printlnc!(yellow: "Interpreter created.");
-                        if let Ok(ref interpreter) = self.interpreter() {
+                        if let Ok(interpreter) = self.interpreter() {
                             display::display_interpreter_properties(interpreter);
                         }
                     },
-                    Err(err) => display_debugger_error(&err),
+                    Err(err) => return Err(err),
                 }
             }
             Command::Infos => {
                 if let Some(ref filename) = self.program_name {
-                    println!("Program in execution: '{}'.", filename);
+                    outln!("Program in execution: '{}'.", filename);
                 }
                 match self.debug_infos() {
-                    Ok(debug) => self.display_infos(&debug, output),
-                    Err(err) => display_debugger_error(&err),
+                    Ok(debug) => self.display_infos(&debug),
+                    Err(err) => return Err(err),
                 }
             },
             Command::Copy(ref filename, ignore_nl) => {
                 self.program_name = Some(filename.clone());
                 match create_program_from_file(&filename, ignore_nl) {
-                    Err(err) => printlnc!(red: "{}", err),
+                    Err(err) => return Err(DebuggerError::Failed(err.to_string())),
                     Ok(program) => {
                         match self.copy_program_and_reset(&program) {
                             Err(_) => { // FIXME if another error than no_interpreter ?!?!
@@ -103,23 +168,23 @@ impl Debugger {
                                 match self.set_interpreter(arch_length, DEFAULT_ARCH_WIDTH) {
                                     Ok(_) => {
                                         printlnc!(yellow: "Interpreter created.");
-                                        if let Ok(ref interpreter) = self.interpreter() {
+                                        if let Ok(interpreter) = self.interpreter() {
                                             display::display_interpreter_properties(interpreter);
                                         }
                                     },
-                                    Err(err) => display_debugger_error(&err),
+                                    Err(err) => return Err(err),
                                 }
                                 self.copy_program_and_reset(&program).unwrap();
                                 match self.debug_infos() {
-                                    Ok(debug) => self.display_infos(&debug, output),
-                                    Err(err) => display_debugger_error(&err),
+                                    Ok(debug) => self.display_infos(&debug),
+                                    Err(err) => return Err(err),
                                 }
                             },
                             Ok(_) => {
                                 printlnc!(yellow: "Program correctly loaded.");
                                 match self.debug_infos() {
-                                    Ok(debug) => self.display_infos(&debug, output),
-                                    Err(err) => display_debugger_error(&err),
+                                    Ok(debug) => self.display_infos(&debug),
+                                    Err(err) => return Err(err),
                                 }
                             },
                         }
@@ -129,49 +194,326 @@ impl Debugger {
             Command::Reset => {
                 match self.reset() {
                     Ok(stat) => {
-                        printlnc!(yellow: "Reset.");
+                        printlnc!(yellow: "Reset, the input is read from the start again.");
                         self.statement = Some(stat);
                         match self.debug_infos() {
-                            Ok(debug) => self.display_infos(&debug, output),
-                            Err(err) => display_debugger_error(&err),
+                            Ok(debug) => self.display_infos(&debug),
+                            Err(err) => return Err(err),
                         }
                     },
-                    Err(err) => display_debugger_error(&err),
+                    Err(err) => return Err(err),
+                }
+            },
+            Command::Step(to_execute) => self.run(to_execute, false)?,
+            Command::Continue(limit) => self.run(limit, true)?,
+            Command::ReverseStep(count) => self.reverse(count, false)?,
+            Command::ReverseContinue(limit) => self.reverse(limit, true)?,
+            Command::Break(ref breakpoint, ref condition) => {
+                let entry = self.breakpoints.insert(breakpoint.clone(), condition.clone());
+                printlnc!(yellow: "{}.", entry);
+            },
+            Command::Delete(None) => {
+                self.breakpoints.clear();
+                printlnc!(yellow: "All breakpoints deleted.");
+            },
+            Command::Delete(Some(id)) => {
+                match self.breakpoints.remove(id) {
+                    Some(entry) => printlnc!(yellow: "{} deleted.", entry),
+                    None => return Err(DebuggerError::Failed(format!("No breakpoint number {}.", id))),
+                }
+            },
+            Command::InfoBreakpoints => {
+                if self.breakpoints.is_empty() {
+                    outln!("No breakpoints.");
+                }
+                for entry in self.breakpoints.iter() {
+                    outln!("{}", entry);
+                }
+            },
+            Command::Print(ref expr) => {
+                match expr.eval(&self.context()?) {
+                    Ok(value) => outln!("{} = {}", expr, value),
+                    Err(err) => return Err(DebuggerError::Failed(err)),
+                }
+            },
+            Command::Assert(ref expr) => {
+                match expr.eval(&self.context()?) {
+                    Ok(0) => return Err(DebuggerError::AssertionFailed(expr.to_string())),
+                    Ok(_) => (),
+                    Err(err) => return Err(DebuggerError::Failed(err)),
+                }
+            },
+            Command::Input(None) => {
+                let remaining = &self.input[self.input_position..];
+                outln!("Input: {} bytes read, {} remaining {}.",
+                         self.input_position, remaining.len(), corpus::quote(remaining));
+            },
+            Command::Input(Some(ref source)) => {
+                match read_input_source(source) {
+                    Ok(bytes) => {
+                        self.input = bytes;
+                        self.input_position = 0;
+                        self.history.clear();
+                        printlnc!(yellow: "Input set to {} bytes.", self.input.len());
+                    },
+                    Err(err) => return Err(DebuggerError::Failed(err.to_string())),
+                }
+            },
+            Command::AppendInput(ref source) => {
+                match read_input_source(source) {
+                    Ok(bytes) => {
+                        self.input.extend(bytes);
+                        printlnc!(yellow: "{} bytes of input remaining.", self.input.len() - self.input_position);
+                    },
+                    Err(err) => return Err(DebuggerError::Failed(err.to_string())),
+                }
+            },
+            Command::Set(register, ref expr) => {
+                let value = expr.eval(&self.context()?);
+                let interpreter = self.interpreter.as_mut().unwrap();
+                let arch_length = interpreter.arch_length() as i64;
+                match (register, value) {
+                    (_, Err(err)) => return Err(DebuggerError::Failed(err)),
+                    (Register::Pc, Ok(value)) => interpreter.set_pc(value.rem_euclid(arch_length) as usize),
+                    (Register::Sp, Ok(value)) => interpreter.set_sp(value.rem_euclid(arch_length) as usize),
+                    (Register::Nz, Ok(value)) => interpreter.set_nz(value != 0),
                 }
+                self.history.clear();
+                self.display_edited();
             },
-            Command::Step(to_execute) => {
-                match self.steps(to_execute, input, output) {
-                    Ok((executed, debug, stat)) => {
-                        self.statement = stat;
-                        if executed == to_execute {
-                            printlnc!(yellow: "{} steps executed.", executed)
-                        } else {
-                            printlnc!(yellow: "{}/{} steps executed.", executed, to_execute)
+            Command::Poke(addr, ref words) => {
+                match self.interpreter {
+                    Some(ref mut interpreter) if addr < interpreter.arch_length() => {
+                        for (i, &word) in words.iter().enumerate() {
+                            interpreter.poke(addr + i, word);
                         }
-                        self.display_infos(&debug, output)
+                        printlnc!(yellow: "{} words written at {:#06x}.", words.len(), addr);
+                    },
+                    Some(ref interpreter) => {
+                        let message = format!("Address {:#06x} is out of the {} words of memory.", addr, interpreter.arch_length());
+                        return Err(DebuggerError::Failed(message))
+                    },
+                    None => return Err(DebuggerError::NoInterpreter),
+                }
+                self.history.clear();
+                self.display_edited();
+            },
+            Command::Disas(start, count) => {
+                match self.debug_infos() {
+                    Ok(debug) => {
+                        let start = start.unwrap_or(debug.pc);
+                        display::display_disassembly(&debug, start, count.unwrap_or(self.pc_lines))
                     },
-                    Err(err) => display_debugger_error(&err),
+                    Err(err) => return Err(err),
                 }
             },
+            Command::Examine(addr, count) => {
+                match self.interpreter() {
+                    Ok(interpreter) => display::display_dump(&interpreter.debug_infos(), interpreter.arch_width(), addr, count),
+                    Err(err) => return Err(err),
+                }
+            },
+            Command::InfoStack => {
+                match self.debug_infos() {
+                    Ok(debug) => display::display_stack(&debug),
+                    Err(err) => return Err(err),
+                }
+            },
+            Command::PcLines(lines) => {
+                self.pc_lines = lines;
+                printlnc!(yellow: "{} instructions displayed from the pc.", lines);
+            },
+            Command::SpLines(lines) => {
+                self.sp_lines = lines;
+                printlnc!(yellow: "{} words displayed from the sp.", lines);
+            },
+            Command::Encoding(None) => outln!("I/O encoding: {}.", self.encoding),
+            Command::Encoding(Some(encoding)) => {
+                self.encoding = encoding;
+                if let Some(ref mut interpreter) = self.interpreter {
+                    interpreter.set_encoding(encoding);
+                }
+                printlnc!(yellow: "I/O encoding set to {}.", encoding);
+            },
+            Command::Dialect(None) => outln!("Dialect: {}.", self.dialect),
+            Command::Dialect(Some(dialect)) => {
+                self.dialect = dialect;
+                if let Some(ref mut interpreter) = self.interpreter {
+                    interpreter.set_dialect(dialect);
+                }
+                printlnc!(yellow: "Dialect set to {}.", dialect);
+            },
+            Command::SaveSession(ref file_name) => {
+                let session = self.session()?;
+                match fs::write(file_name, session.to_string()) {
+                    Ok(_) => printlnc!(yellow: "Session saved to '{}'.", file_name),
+                    Err(err) => return Err(DebuggerError::Failed(format!("{}: {}", file_name, err))),
+                }
+            },
+            Command::LoadSession(ref file_name) => {
+                let session = fs::read_to_string(file_name).map_err(|err| err.to_string())
+                                  .and_then(|source| source.parse())
+                                  .map_err(|err| DebuggerError::Failed(format!("{}: {}", file_name, err)))?;
+                self.load_session(session)?;
+                printlnc!(yellow: "Session loaded from '{}'.", file_name);
+                self.display_edited();
+            },
+            Command::Record(Some(ref file_name)) => {
+                match File::create(file_name) {
+                    Ok(file) => {
+                        self.recording = Some(file);
+                        printlnc!(yellow: "Recording the commands to '{}'.", file_name);
+                    },
+                    Err(err) => return Err(DebuggerError::Failed(format!("{}: {}", file_name, err))),
+                }
+            },
+            Command::Record(None) => {
+                match self.recording.take() {
+                    Some(_) => printlnc!(yellow: "Recording stopped."),
+                    None => return Err(DebuggerError::Failed("No recording in progress.".to_string())),
+                }
+            },
+            Command::Replay(ref file_name) => {
+                let file = File::open(file_name).map_err(|err| DebuggerError::Failed(format!("{}: {}", file_name, err)))?;
+                // the replay command is recorded rather than the commands it runs
+                let recording = self.recording.take();
+                let result = self.execute_lines(file_name, BufReader::new(file));
+                self.recording = recording;
+                result?;
+            },
             Command::Exit | Command::Repeat => unreachable!(),
         };
+        Ok(())
+    }
+
+    /// Parse and execute a line, an empty line repeats the last command,
+    /// the command is appended to the recording once executed.
+    pub fn execute_line(&mut self, line: &str) -> Result<Flow, DebuggerError> {
+        let (command, line) = match line.parse() {
+            Ok(Command::Repeat) => self.last_command.take().ok_or_else(|| DebuggerError::Failed("No last command.".to_string()))?,
+            Ok(command) => (command, line.trim().to_string()),
+            Err(err) => {
+                self.last_command = None;
+                return Err(DebuggerError::Failed(err.into_owned()))
+            },
+        };
+        if let Command::Exit = command {
+            return Ok(Flow::Exit)
+        }
+        let result = self.execute(&command);
+        let recorded = result.is_ok() && !matches!(command, Command::Record(_));
+        self.last_command = Some((command, line.clone()));
+        result?;
+        if recorded {
+            self.record(&line)?;
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn record(&mut self, line: &str) -> Result<(), DebuggerError> {
+        if let Some(ref mut file) = self.recording {
+            if let Err(err) = writeln!(file, "{}", line) {
+                self.recording = None;
+                return Err(DebuggerError::Failed(format!("Recording stopped: {}", err)))
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute the lines of a script until an `exit`, blank lines and `#` comments are ignored,
+    /// stop on the first error and report it with the line it comes from.
+    pub fn execute_lines<R: BufRead>(&mut self, file_name: &str, reader: R) -> Result<(), DebuggerError> {
+        for (number, line) in reader.lines().enumerate() {
+            let failed = |err: &dyn fmt::Display| DebuggerError::Failed(format!("{}:{}: {}", file_name, number + 1, err));
+            let line = line.map_err(|err| failed(&err))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            match self.execute_line(line) {
+                Ok(Flow::Continue) => (),
+                Ok(Flow::Exit) => break,
+                Err(err) => return Err(failed(&err)),
+            }
+        }
+        Ok(())
+    }
+
+    fn session(&self) -> Result<Session, DebuggerError> {
+        let interpreter = self.interpreter()?;
+        Ok(Session {
+            program: self.program_name.clone(),
+            arch_length: interpreter.arch_length(),
+            arch_width: interpreter.arch_width(),
+            encoding: self.encoding,
+            dialect: self.dialect,
+            pc: interpreter.pc(),
+            sp: interpreter.sp(),
+            nz: interpreter.nz(),
+            cycles: self.number_of_cycles,
+            memory: interpreter.memory().to_vec(),
+            input: self.input.clone(),
+            input_position: self.input_position,
+            output: self.output.clone(),
+            output_length: self.output_length,
+            breakpoints: self.breakpoints.iter().map(Entry::command).collect(),
+        })
+    }
+
+    fn load_session(&mut self, session: Session) -> Result<(), DebuggerError> {
+        let mut breakpoints = Vec::new();
+        for line in &session.breakpoints {
+            match line.parse() {
+                Ok(Command::Break(breakpoint, condition)) => breakpoints.push((breakpoint, condition)),
+                _ => return Err(DebuggerError::Failed(format!("invalid breakpoint {:?}", line))),
+            }
+        }
+
+        self.encoding = session.encoding;
+        self.dialect = session.dialect;
+        self.set_interpreter(session.arch_length, session.arch_width)?;
+        let interpreter = self.interpreter.as_mut().unwrap();
+        for (addr, &word) in session.memory.iter().enumerate() {
+            interpreter.poke(addr, word);
+        }
+        interpreter.set_pc(session.pc);
+        interpreter.set_sp(session.sp);
+        interpreter.set_nz(session.nz);
+
+        self.program_name = session.program;
+        self.number_of_cycles = session.cycles;
+        self.input = session.input;
+        self.input_position = session.input_position;
+        self.output = session.output;
+        self.output_length = session.output_length;
+        self.statement = None;
+        self.breakpoints.clear();
+        for (breakpoint, condition) in breakpoints {
+            self.breakpoints.insert(breakpoint, condition);
+        }
+        Ok(())
     }
 
     fn set_interpreter(&mut self, arch_length: usize, arch_width: usize) -> Result<(), DebuggerError> {
-        let interpreter = match Interpreter::new(arch_length, arch_width) {
+        let mut interpreter = match Interpreter::new(arch_length, arch_width) {
             Err(err) => return Err(DebuggerError::InterpreterCreation(err)),
             Ok(interpreter) => interpreter
         };
+        interpreter.enable_memory_watcher();
+        interpreter.set_encoding(self.encoding);
+        interpreter.set_dialect(self.dialect);
         self.interpreter = Some(interpreter);
+        self.history.clear();
         Ok(())
     }
 
     fn unset_interpreter(&mut self) -> Result<(), DebuggerError> {
-        if let None = self.interpreter {
+        if self.interpreter.is_none() {
             Err(DebuggerError::NoInterpreter)
         }
         else {
             self.interpreter = None;
+            self.history.clear();
             Ok(())
         }
     }
@@ -187,6 +529,8 @@ impl Debugger {
         if let Some(ref mut interpreter) = self.interpreter {
             interpreter.copy_program(program);
             interpreter.reset();
+            self.input_position = 0;
+            self.history.clear();
             Ok(())
         }
         else { Err(DebuggerError::NoInterpreter) }
@@ -194,42 +538,165 @@ impl Debugger {
 
     fn reset(&mut self) -> Result<Statement, DebuggerError> {
         if let Some(ref mut interpreter) = self.interpreter {
+            self.input_position = 0;
+            self.history.clear();
             Ok(interpreter.reset())
         }
         else { Err(DebuggerError::NoInterpreter) }
     }
 
-    fn steps<R: ?Sized + Read, W: ?Sized + Write>(&mut self, steps: usize, input: &mut R, output: &mut W)
-        -> Result<(usize, DebugInfos, Option<Statement>), DebuggerError> {
+    fn run(&mut self, limit: usize, is_continue: bool) -> Result<(), DebuggerError> {
+        match self.steps(limit) {
+            Ok((executed, debug, stat, stop)) => {
+                self.statement = stat;
+                match stop {
+                    Some(Stop::Breakpoint(hit)) => printlnc!(yellow: "{}, hit after {} steps.", hit, executed),
+                    Some(Stop::Halt) if is_continue => printlnc!(yellow: "Halted after {} steps.", executed),
+                    None if is_continue => printlnc!(yellow: "Cycle limit of {} steps reached.", executed),
+                    _ if executed == limit => printlnc!(yellow: "{} steps executed.", executed),
+                    _ => printlnc!(yellow: "{}/{} steps executed.", executed, limit),
+                }
+                self.display_infos(&debug);
+                display::display_memory_events(&self.drain_memory_events());
+                Ok(())
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Execute at most `steps` instructions, stop on a `HALT` or on a breakpoint,
+    /// a breakpoint on the instruction executed first is ignored.
+    fn steps(&mut self, steps: usize)
+        -> Result<(usize, DebugInfos, Option<Statement>, Option<Stop>), DebuggerError> {
 
         if let Some(ref mut interpreter) = self.interpreter {
             let mut statement = None;
             let mut executed = 0;
+            let mut stop = None;
             for i in 0..steps {
-                statement = Some(interpreter.step(input, output));
-                if let Some(statement) = statement {
-                    match statement {
-                        Statement(op_codes::HALT, _) => break,
-                        _ => (),
+                let context = Context { interpreter, cycles: self.number_of_cycles + executed, output: self.output_length };
+                if i != 0 {
+                    if let Some(hit) = self.breakpoints.hit_before(&context) {
+                        stop = Some(Stop::Breakpoint(hit));
+                        break
                     }
                 }
+                let watched = self.breakpoints.watched(&context);
+                let (nz, input_position) = (interpreter.nz(), self.input_position);
+                let (output_bytes, output_length) = (self.output.len(), self.output_length);
+                let previous_statement = statement.or(self.statement);
+
+                let mut overwritten = Overwritten::default();
+                let mut input = &self.input[self.input_position..];
+                let details = interpreter.step_detailed_with(&mut input, &mut self.output, &mut overwritten);
+                self.input_position = self.input.len() - input.len();
+                statement = Some(details.statement);
+                if details.output.is_some() {
+                    self.output_length += 1;
+                }
+                if let Statement(op_codes::HALT, _) = details.statement {
+                    stop = Some(Stop::Halt);
+                    break
+                }
                 executed = i + 1;
+                if self.history.len() == HISTORY_LENGTH {
+                    self.history.pop_front();
+                }
+                self.history.push_back(Undo {
+                    details,
+                    nz,
+                    overwritten: overwritten.0,
+                    input_position,
+                    output_bytes,
+                    output_length,
+                    statement: previous_statement,
+                });
+                let context = Context { interpreter, cycles: self.number_of_cycles + executed, output: self.output_length };
+                if let Some(hit) = self.breakpoints.hit_after(&details, &watched, &context) {
+                    stop = Some(Stop::Breakpoint(hit));
+                    break
+                }
             }
             self.number_of_cycles += executed;
-            Ok((executed, interpreter.debug_infos(), statement))
+            Ok((executed, interpreter.debug_infos(), statement, stop))
         }
         else { Err(DebuggerError::NoInterpreter) }
     }
 
-    fn display_infos<D: ?Sized + Debug>(&self, debug_infos: &DebugInfos, output: &D) {
+    /// Undo `limit` steps at most, stop on a breakpoint or at the start of the history.
+    fn reverse(&mut self, limit: usize, is_continue: bool) -> Result<(), DebuggerError> {
+        let mut undone = 0;
+        let mut hit = None;
+        while undone < limit {
+            let new = self.breakpoints.watched(&self.context()?);
+            let details = match self.undo_step() {
+                Some(details) => details,
+                None => break,
+            };
+            undone += 1;
+            let context = self.context()?;
+            hit = self.breakpoints.hit_undone(&details, &new, &context)
+                      .or_else(|| self.breakpoints.hit_before(&context));
+            if hit.is_some() {
+                break
+            }
+        }
+
+        match hit {
+            Some(hit) => printlnc!(yellow: "{}, hit after {} steps back.", hit, undone),
+            None if undone < limit => printlnc!(yellow: "Start of the history reached after {} steps back.", undone),
+            None if is_continue => printlnc!(yellow: "Cycle limit of {} steps back reached.", undone),
+            None => printlnc!(yellow: "{} steps back.", undone),
+        }
+        self.display_edited();
+        Ok(())
+    }
+
+    /// Restore the state before the last step executed.
+    fn undo_step(&mut self) -> Option<StepDetails> {
+        let interpreter = self.interpreter.as_mut()?;
+        let undo = self.history.pop_back()?;
+        for &(addr, word) in undo.overwritten.iter().rev() {
+            interpreter.poke(addr, word);
+        }
+        interpreter.set_pc(undo.details.pc);
+        interpreter.set_sp(undo.details.sp);
+        interpreter.set_nz(undo.nz);
+        self.input_position = undo.input_position;
+        self.output.truncate(undo.output_bytes);
+        self.output_length = undo.output_length;
+        self.statement = undo.statement;
+        self.number_of_cycles -= 1;
+        Some(undo.details)
+    }
+
+    fn context(&self) -> Result<Context<'_>, DebuggerError> {
+        let interpreter = self.interpreter()?;
+        Ok(Context { interpreter, cycles: self.number_of_cycles, output: self.output_length })
+    }
+
+    fn drain_memory_events(&mut self) -> Vec<MemoryEvent> {
+        match self.interpreter {
+            Some(ref mut interpreter) => interpreter.drain_memory_events(),
+            None => Vec::new(),
+        }
+    }
+
+    fn display_infos(&self, debug_infos: &DebugInfos) {
         display::display_infos(debug_infos,
                                self.number_of_cycles,
                                self.statement,
-                               output,
+                               &self.output,
                                self.pc_lines,
                                self.sp_lines)
     }
 
+    fn display_edited(&self) {
+        if let Ok(debug) = self.debug_infos() {
+            self.display_infos(&debug);
+        }
+    }
+
     fn debug_infos(&self) -> Result<DebugInfos, DebuggerError> {
         if let Some(ref interpreter) = self.interpreter {
             Ok(interpreter.debug_infos())
@@ -1,14 +1,29 @@
+use std::fmt;
+
 #[derive(Debug)]
 pub enum DebuggerError {
     NoInterpreter,
-    InterpreterCreation(&'static str)
+    InterpreterCreation(&'static str),
+    /// A command could not be executed.
+    Failed(String),
+    /// The expression of an `assert` evaluated to zero.
+    AssertionFailed(String),
 }
 
-impl DebuggerError {
-    pub fn description(&self) -> &'static str {
+impl fmt::Display for DebuggerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            DebuggerError::NoInterpreter => "No interpreter created",
-            DebuggerError::InterpreterCreation(err) => err
+            DebuggerError::NoInterpreter => write!(f, "No interpreter created"),
+            DebuggerError::InterpreterCreation(err) => write!(f, "{}", err),
+            DebuggerError::Failed(ref err) => write!(f, "{}", err),
+            DebuggerError::AssertionFailed(ref expr) => write!(f, "Assertion failed: {}", expr),
         }
     }
 }
+
+pub fn display_debugger_error(dbg_err: &DebuggerError) {
+    printlnc!(red: "{}", dbg_err);
+    if let DebuggerError::NoInterpreter = *dbg_err {
+        printlnc!(yellow: "{}", "`interpreter [arch_length] [arch_width]` to create one")
+    }
+}
@@ -1,30 +1,39 @@
 use std::fmt::Debug;
-use reustmann::{DebugInfos, Statement, Interpreter};
-use reustmann::instruction::{Instruction, LongMnemonic, Mnemonic, OpCode, is_valid_op_code};
+use reustmann::{DebugInfos, Statement, Interpreter, MemoryEvent, MemoryEventKind, Word};
+use reustmann::instruction::{Instruction, LongMnemonic, Mnemonic, is_valid_op_code};
+use reustmann::memory::decode;
 
-fn is_visible(c: u8) -> bool {
-    c >= 32 && c <= 126
+use term::strip_colors;
+
+fn is_visible(c: Word) -> bool {
+    (32..=126).contains(&c)
+}
+
+fn long_mnemonic(word: Word) -> LongMnemonic {
+    Into::<Instruction>::into(decode(word)).into()
 }
 
 pub fn display_statement(statement: Option<Statement>) {
     if let Some(statement) = statement {
         let Statement(op_code, is_success) = statement;
         let name: LongMnemonic = Into::<Instruction>::into(op_code).into();
-        println!("Last instruction was '{}' and return '{}'.", name, is_success);
+        outln!("Last instruction was '{}' and return '{}'.", name, is_success);
     }
 }
 
-pub fn format_program_counter(mem_addr: usize, offset: usize, op_code: OpCode) -> String {
+pub fn format_program_counter(mem_addr: usize, offset: usize, word: Word) -> String {
+    let op_code = decode(word);
     let instr: Instruction = op_code.into();
     let longmnemo: LongMnemonic = instr.into();
     let mem_addr = format!(colorify!(blue: "{:>#06x}"), mem_addr);
 
-    let (op_code, longmnemo) = if is_valid_op_code(op_code) {
+    let (op_code, longmnemo) = if is_valid_op_code(op_code) && word == op_code as Word {
         let op = format!("{:#04x},  {} ", op_code, Into::<Mnemonic>::into(instr));
         let name = format!(colorify!(green: "{:<6}"), longmnemo);
         (op, name)
     } else {
-        let op = format!("{:#04x}, '{}'", op_code, op_code as char);
+        let preview = std::char::from_u32(word).unwrap_or(std::char::REPLACEMENT_CHARACTER);
+        let op = format!("{:#04x}, {:?}", word, preview);
         let name = format!(colorify!(red: "{:<6}"), longmnemo);
         (op, name)
     };
@@ -32,10 +41,10 @@ pub fn format_program_counter(mem_addr: usize, offset: usize, op_code: OpCode) -
     format!("{} <{:+}>: {} ({})", mem_addr, offset, longmnemo, op_code)
 }
 
-pub fn format_stack_pointer(mem_addr: usize, value: u8) -> String {
+pub fn format_stack_pointer(mem_addr: usize, value: Word) -> String {
     let mem_addr = format!(colorify!(blue: "{:>#06x}"), mem_addr);
-    if is_visible(value) == true {
-        let preview = value as char;
+    if is_visible(value) {
+        let preview = value as u8 as char;
         format!("{} ({:#04x}, '{}')", mem_addr, value, preview)
     }
     else {
@@ -43,28 +52,32 @@ pub fn format_stack_pointer(mem_addr: usize, value: u8) -> String {
     }
 }
 
-fn display_sides(instr: Option<(usize, (usize, &u8))>,
-                 stack: Option<(usize, &u8)>,
-                 indicators: bool) {
+// the width of a string once printed, without the color escapes
+fn visible_width(s: &str) -> usize {
+    strip_colors(s).chars().count()
+}
+
+fn format_sides(instr: Option<(usize, (usize, &Word))>,
+                stack: Option<(usize, &Word)>,
+                indicators: bool) -> (String, String) {
 
     let pc_side = if let Some((idx, (pc_addr, op_code))) = instr {
         let pc_side = format_program_counter(pc_addr, idx, *op_code);
-        if indicators == true { format!("{} {}", colorify!(red: "pc"), pc_side) }
+        if indicators { format!("{} {}", colorify!(red: "pc"), pc_side) }
         else { format!("   {}", pc_side) }
     } else {
-        format!("")
+        String::new()
     };
     let sp_side = if let Some((sp_addr, value)) = stack {
         let sp_side = format_stack_pointer(sp_addr, *value);
-        if indicators == true { format!("{} {}", colorify!(red: "sp"), sp_side) }
+        if indicators { format!("{} {}", colorify!(red: "sp"), sp_side) }
         else { format!("   {}", sp_side) }
     } else {
-        format!("")
+        String::new()
     };
-    println!("{}    {}", pc_side, sp_side);
+    (pc_side, sp_side)
 }
 
-// FIXME ugly really !!!
 pub fn display_infos<D: ?Sized + Debug>(debug_infos: &DebugInfos,
                                         number_of_cycles: usize,
                                         statement: Option<Statement>,
@@ -72,30 +85,108 @@ pub fn display_infos<D: ?Sized + Debug>(debug_infos: &DebugInfos,
                                         pc_lines: usize,
                                         sp_lines: usize) {
 
-    // if let Some(output) = output {
-        // let output = String::from_utf8_lossy(&output);
-        println!("Output: {:?}", output);
-    // }
+    outln!("Output: {:?}", output);
 
     let &DebugInfos{ ref memory, pc, sp, nz } = debug_infos;
-    println!("cycles: {}, pc: {}, sp: {}, nz: {}", number_of_cycles, pc, sp, nz);
+    outln!("cycles: {}, pc: {}, sp: {}, nz: {}", number_of_cycles, pc, sp, nz);
     display_statement(statement);
 
     let mut instrs = (*memory).iter().enumerate().cycle().skip(pc).take(pc_lines).enumerate();
     let mut stack = (*memory).iter().enumerate().cycle().skip(sp).take(sp_lines);
 
-    display_sides(instrs.next(), stack.next(), true);
+    let mut rows = vec![format_sides(instrs.next(), stack.next(), true)];
     loop {
         match (instrs.next(), stack.next()) {
             (None, None) => break,
-            (instr, stack) => display_sides(instr, stack, false),
+            (instr, stack) => rows.push(format_sides(instr, stack, false)),
+        }
+    }
+    let width = rows.iter().map(|(pc_side, _)| visible_width(pc_side)).max().unwrap_or(0);
+    for (pc_side, sp_side) in rows {
+        let padding = width - visible_width(&pc_side);
+        let row = format!("{}{:padding$}    {}", pc_side, "", sp_side, padding = padding);
+        outln!("{}", row.trim_end());
+    }
+}
+
+/// Display `count` instructions from `start`, marking the PC and the SP.
+pub fn display_disassembly(debug_infos: &DebugInfos, start: usize, count: usize) {
+    let &DebugInfos{ ref memory, pc, sp, .. } = debug_infos;
+    let lines = memory.iter().enumerate().cycle().skip(start % memory.len()).take(count);
+    for (offset, (addr, &word)) in lines.enumerate() {
+        let marker = match (addr == pc, addr == sp) {
+            (true, true) => colorify!(red: "pc,sp"),
+            (true, false) => colorify!(red: "pc   "),
+            (false, true) => colorify!(red: "   sp"),
+            (false, false) => "     ",
+        };
+        outln!("{} {}", marker, format_program_counter(addr, offset, word));
+    }
+}
+
+const DUMP_WORDS_PER_LINE: usize = 8;
+
+/// Display `count` words from `start` in hexadecimal and ASCII,
+/// with the mnemonics of the op codes under them.
+pub fn display_dump(debug_infos: &DebugInfos, arch_width: usize, start: usize, count: usize) {
+    let memory = &debug_infos.memory;
+    let digits = arch_width.div_ceil(4);
+    let words: Vec<_> = memory.iter().enumerate().cycle().skip(start % memory.len()).take(count).collect();
+
+    for line in words.chunks(DUMP_WORDS_PER_LINE) {
+        let mut hex = String::new();
+        let mut ascii = String::new();
+        let mut mnemonics = String::new();
+        for &(_, &word) in line {
+            hex.push_str(&format!("{:0digits$x} ", word, digits = digits));
+            ascii.push(if is_visible(word) { word as u8 as char } else { '.' });
+            let op_code = decode(word);
+            let mnemo = if is_valid_op_code(op_code) && word == op_code as Word {
+                Into::<Mnemonic>::into(Into::<Instruction>::into(op_code))
+            } else { ' ' };
+            mnemonics.push_str(&format!("{:<width$}", mnemo, width = digits + 1));
+        }
+        let hex_width = DUMP_WORDS_PER_LINE * (digits + 1);
+        outln!(concat!(colorify!(blue: "{:>#06x}"), ": {:<hex_width$} |{}|"), line[0].0, hex, ascii, hex_width = hex_width);
+        if !mnemonics.trim().is_empty() {
+            outln!(concat!("        ", colorify!(green: "{}")), mnemonics.trim_end());
+        }
+    }
+}
+
+/// Display the words pushed since the reset, from the SP to the end of memory.
+pub fn display_stack(debug_infos: &DebugInfos) {
+    let &DebugInfos{ ref memory, sp, .. } = debug_infos;
+    if sp == 0 {
+        outln!("The stack is empty.");
+        return
+    }
+    outln!("{} words on the stack.", memory.len() - sp);
+    for (depth, (addr, value)) in memory.iter().enumerate().skip(sp).enumerate() {
+        outln!("#{:<4} {}", depth, format_stack_pointer(addr, *value));
+    }
+}
+
+pub fn display_memory_events(events: &[MemoryEvent]) {
+    for event in events {
+        match event.kind {
+            MemoryEventKind::StackOverwroteCode { old, new } => {
+                let (old, new) = (long_mnemonic(old), long_mnemonic(new));
+                printlnc!(red: "Stack overwrote code at {:#06x} (pc: {}): {} -> {}.", event.addr, event.pc, old, new);
+            },
+            MemoryEventKind::ModifiedCodeExecuted { op_code } => {
+                let name: LongMnemonic = Into::<Instruction>::into(op_code).into();
+                printlnc!(red: "Modified code executed at {:#06x}: {}.", event.addr, name);
+            },
         }
     }
 }
 
 pub fn display_interpreter_properties(interpreter: &Interpreter) {
-    println!("Interpreter as an arch width of {} and an arch length of {}.",
-        format!(colorify!(yellow: "{}"), interpreter.arch_width()),
-        format!(colorify!(yellow: "{}"), interpreter.arch_length())
+    outln!(concat!("Interpreter as an arch width of ", colorify!(yellow: "{}"),
+                     " and an arch length of ", colorify!(yellow: "{}"), "."),
+        interpreter.arch_width(),
+        interpreter.arch_length()
     );
+    outln!(concat!("It runs the ", colorify!(yellow: "{}"), " dialect."), interpreter.dialect());
 }
@@ -0,0 +1,342 @@
+//! The expressions of the debugger conditions and displays, like
+//! `mem[sp + 1] == 'H' && nz`.
+//!
+//! - numbers are decimal, `0x` hexadecimal or quoted chars like `'H'`,
+//!   `true` and `false` are `1` and `0`,
+//! - `pc`, `sp`, `nz`, `cycles` and `out`, the number of words written,
+//! - `mem[addr]`, the address is taken modulo the arch length,
+//! - `tos` and `nos` for `mem[sp]` and `mem[sp + 1]`, `stack[n]` for `mem[sp + n]`,
+//! - `+ - * / %`, `== != < <= > >=`, `&& || !` with the precedence of C.
+//!
+//! Comparisons and boolean operators evaluate to `1` or `0`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use reustmann::Interpreter;
+
+/// What an expression can read.
+pub struct Context<'a> {
+    pub interpreter: &'a Interpreter,
+    pub cycles: usize,
+    /// The number of words written to the output.
+    pub output: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var { Pc, Sp, Nz, Cycles, Output }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinOp { Add, Sub, Mul, Div, Rem, Eq, Ne, Lt, Le, Gt, Ge, And, Or }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Number(i64),
+    Var(Var),
+    /// `mem[sp + offset]`.
+    Stack(Box<Node>),
+    Mem(Box<Node>),
+    Not(Box<Node>),
+    Neg(Box<Node>),
+    Binary(BinOp, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Punct(&'static str),
+}
+
+const PUNCTS: [&str; 20] = ["==", "!=", "<=", ">=", "&&", "||",
+                            "<", ">", "!", "+", "-", "*", "/", "%", "(", ")", "[", "]", "=", "&"];
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c.is_ascii_digit() {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+            let (number, radix) = match rest[..end].strip_prefix("0x") {
+                Some(hex) => (hex, 16),
+                None => (&rest[..end], 10),
+            };
+            let value = i64::from_str_radix(number, radix).map_err(|_| format!("invalid number {:?}", &rest[..end]))?;
+            tokens.push(Token::Number(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else if c == '\'' {
+            let mut chars = rest[1..].chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), Some('\'')) => tokens.push(Token::Number(c as i64)),
+                _ => return Err("invalid char literal".to_string()),
+            }
+            rest = chars.as_str();
+        } else {
+            match PUNCTS.iter().find(|p| rest.starts_with(*p)) {
+                Some(&"=") | Some(&"&") => return Err(format!("unexpected `{}`, did you mean `{}{}`", c, c, c)),
+                Some(p) => {
+                    tokens.push(Token::Punct(p));
+                    rest = &rest[p.len()..];
+                },
+                None => return Err(format!("unexpected `{}`", c)),
+            }
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Ident(ref ident) => f.write_str(ident),
+            Token::Punct(p) => f.write_str(p),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_punct(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(&Token::Punct(p)) => Some(p),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        match self.peek_punct() {
+            Some(p) if p == punct => { self.pos += 1; Ok(()) },
+            _ => Err(format!("expected {:?}", punct)),
+        }
+    }
+
+    // parse the operators of a precedence level, from the lowest one
+    fn binary(&mut self, level: usize) -> Result<Node, String> {
+        const LEVELS: [&[(&str, BinOp)]; 6] = [
+            &[("||", BinOp::Or)],
+            &[("&&", BinOp::And)],
+            &[("==", BinOp::Eq), ("!=", BinOp::Ne)],
+            &[("<", BinOp::Lt), ("<=", BinOp::Le), (">", BinOp::Gt), (">=", BinOp::Ge)],
+            &[("+", BinOp::Add), ("-", BinOp::Sub)],
+            &[("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Rem)],
+        ];
+        if level == LEVELS.len() {
+            return self.unary()
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(&(_, op)) = self.peek_punct().and_then(|p| LEVELS[level].iter().find(|&&(s, _)| s == p)) {
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.peek_punct() {
+            Some("!") => { self.pos += 1; Ok(Node::Not(Box::new(self.unary()?))) },
+            Some("-") => { self.pos += 1; Ok(Node::Neg(Box::new(self.unary()?))) },
+            _ => self.primary(),
+        }
+    }
+
+    fn indexed(&mut self) -> Result<Node, String> {
+        self.expect("[")?;
+        let index = self.binary(0)?;
+        self.expect("]")?;
+        Ok(index)
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("unexpected end of expression")?;
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Punct("(") => {
+                let node = self.binary(0)?;
+                self.expect(")")?;
+                Ok(node)
+            },
+            Token::Punct(p) => Err(format!("unexpected `{}`", p)),
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Node::Number(1)),
+                "false" => Ok(Node::Number(0)),
+                "pc" => Ok(Node::Var(Var::Pc)),
+                "sp" => Ok(Node::Var(Var::Sp)),
+                "nz" => Ok(Node::Var(Var::Nz)),
+                "cycles" => Ok(Node::Var(Var::Cycles)),
+                "out" => Ok(Node::Var(Var::Output)),
+                "tos" => Ok(Node::Stack(Box::new(Node::Number(0)))),
+                "nos" => Ok(Node::Stack(Box::new(Node::Number(1)))),
+                "stack" => Ok(Node::Stack(Box::new(self.indexed()?))),
+                "mem" => Ok(Node::Mem(Box::new(self.indexed()?))),
+                _ => Err(format!("unknown name {:?}", ident)),
+            },
+        }
+    }
+}
+
+fn eval(node: &Node, context: &Context) -> Result<i64, String> {
+    let interpreter = context.interpreter;
+    let load = |addr: i64| {
+        let addr = addr.rem_euclid(interpreter.arch_length() as i64) as usize;
+        interpreter.memory()[addr] as i64
+    };
+    Ok(match *node {
+        Node::Number(value) => value,
+        Node::Var(Var::Pc) => interpreter.pc() as i64,
+        Node::Var(Var::Sp) => interpreter.sp() as i64,
+        Node::Var(Var::Nz) => interpreter.nz() as i64,
+        Node::Var(Var::Cycles) => context.cycles as i64,
+        Node::Var(Var::Output) => context.output as i64,
+        Node::Stack(ref offset) => load(interpreter.sp() as i64 + eval(offset, context)?),
+        Node::Mem(ref addr) => load(eval(addr, context)?),
+        Node::Not(ref node) => (eval(node, context)? == 0) as i64,
+        Node::Neg(ref node) => eval(node, context)?.wrapping_neg(),
+        Node::Binary(BinOp::And, ref lhs, ref rhs) => (eval(lhs, context)? != 0 && eval(rhs, context)? != 0) as i64,
+        Node::Binary(BinOp::Or, ref lhs, ref rhs) => (eval(lhs, context)? != 0 || eval(rhs, context)? != 0) as i64,
+        Node::Binary(op, ref lhs, ref rhs) => {
+            let (a, b) = (eval(lhs, context)?, eval(rhs, context)?);
+            match op {
+                BinOp::Add => a.wrapping_add(b),
+                BinOp::Sub => a.wrapping_sub(b),
+                BinOp::Mul => a.wrapping_mul(b),
+                BinOp::Div => a.checked_div(b).ok_or("division by zero")?,
+                BinOp::Rem => a.checked_rem(b).ok_or("division by zero")?,
+                BinOp::Eq => (a == b) as i64,
+                BinOp::Ne => (a != b) as i64,
+                BinOp::Lt => (a < b) as i64,
+                BinOp::Le => (a <= b) as i64,
+                BinOp::Gt => (a > b) as i64,
+                BinOp::Ge => (a >= b) as i64,
+                BinOp::And | BinOp::Or => unreachable!(),
+            }
+        },
+    })
+}
+
+/// A parsed expression, displayed as it was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr {
+    source: String,
+    root: Node,
+}
+
+impl Expr {
+    pub fn eval(&self, context: &Context) -> Result<i64, String> {
+        eval(&self.root, context)
+    }
+}
+
+impl FromStr for Expr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(s)?, pos: 0 };
+        let root = parser.binary(0)?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected `{}` after the expression", token))
+        }
+        Ok(Expr { source: s.trim().to_string(), root })
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Node {
+        s.parse::<Expr>().unwrap().root
+    }
+
+    fn binary(op: BinOp, lhs: Node, rhs: Node) -> Node {
+        Node::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    fn eval_with(s: &str, interpreter: &Interpreter) -> Result<i64, String> {
+        let context = Context { interpreter, cycles: 3, output: 2 };
+        s.parse::<Expr>()?.eval(&context)
+    }
+
+    fn eval(s: &str) -> Result<i64, String> {
+        eval_with(s, &Interpreter::new(16, 8).unwrap())
+    }
+
+    #[test]
+    fn precedence() {
+        use self::Node::Number;
+
+        assert_eq!(parse("1 + 2 * 3"), binary(BinOp::Add, Number(1), binary(BinOp::Mul, Number(2), Number(3))));
+        assert_eq!(parse("1 - 2 - 3"), binary(BinOp::Sub, binary(BinOp::Sub, Number(1), Number(2)), Number(3)));
+        assert_eq!(parse("1 == 2 < 3"), binary(BinOp::Eq, Number(1), binary(BinOp::Lt, Number(2), Number(3))));
+        assert_eq!(parse("1 < 2 != 3 >= 4"),
+                   binary(BinOp::Ne, binary(BinOp::Lt, Number(1), Number(2)), binary(BinOp::Ge, Number(3), Number(4))));
+        assert_eq!(parse("1 || 2 && 3"), binary(BinOp::Or, Number(1), binary(BinOp::And, Number(2), Number(3))));
+        assert_eq!(parse("!1 == -2"),
+                   binary(BinOp::Eq, Node::Not(Box::new(Number(1))), Node::Neg(Box::new(Number(2)))));
+    }
+
+    #[test]
+    fn evaluation() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9));
+        assert_eq!(eval("7 / 2 + 7 % 2"), Ok(4));
+        assert_eq!(eval("1 == 2 < 3"), Ok(1));
+        assert_eq!(eval("2 > 1 == 1"), Ok(1));
+        assert_eq!(eval("!0 && -1 || false"), Ok(1));
+        assert_eq!(eval("'H' + 0x10"), Ok(88));
+        assert_eq!(eval("cycles * 10 + out"), Ok(32));
+    }
+
+    #[test]
+    fn memory_access() {
+        let mut interpreter = Interpreter::new(16, 8).unwrap();
+        interpreter.poke(0, 9);
+        interpreter.poke(3, 72);
+        interpreter.poke(4, 5);
+        interpreter.set_sp(3);
+
+        assert_eq!(eval_with("mem[sp+1]", &interpreter), Ok(5));
+        assert_eq!(eval_with("mem[sp] == 'H' && nos == 5", &interpreter), Ok(1));
+        assert_eq!(eval_with("tos + stack[1]", &interpreter), Ok(77));
+        assert_eq!(eval_with("mem[16] + mem[-13]", &interpreter), Ok(81));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(eval("1 / 0"), Err("division by zero".to_string()));
+        assert_eq!(eval("1 % (2 - 2)"), Err("division by zero".to_string()));
+        assert_eq!(eval("0 && 1 / 0"), Ok(0));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(eval("1 +"), Err("unexpected end of expression".to_string()));
+        assert_eq!(eval("(1"), Err("expected \")\"".to_string()));
+        assert_eq!(eval("mem[1"), Err("expected \"]\"".to_string()));
+        assert_eq!(eval("1 2"), Err("unexpected `2` after the expression".to_string()));
+        assert_eq!(eval("pc = 1"), Err("unexpected `=`, did you mean `==`".to_string()));
+        assert_eq!(eval("pc & 1"), Err("unexpected `&`, did you mean `&&`".to_string()));
+        assert_eq!(eval("foo"), Err("unknown name \"foo\"".to_string()));
+        assert_eq!(eval("0xg"), Err("invalid number \"0xg\"".to_string()));
+        assert_eq!(eval("'ab'"), Err("invalid char literal".to_string()));
+        assert_eq!(eval("1 $ 2"), Err("unexpected `$`".to_string()));
+        assert_eq!(eval(")"), Err("unexpected `)`".to_string()));
+    }
+}
@@ -0,0 +1,479 @@
+//! A stub speaking the GDB Remote Serial Protocol over TCP.
+//!
+//! The registers are the PC, the SP and the NZ flag, 32 bits each, and the L words
+//! are the memory. A word takes `ceil(W / 8)` little endian bytes, so the PC and the
+//! SP are given as byte addresses, the word index times the bytes of a word.
+//! The breakpoints are the `Z0` software breakpoints, the program reads the
+//! standard input and writes to the standard output.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use reustmann::{Dialect, Encoding, Interpreter, Program, Statement, Word};
+use reustmann::instruction::op_codes;
+use reustmann::memory::decode;
+
+const DEFAULT_PORT: u16 = 1234;
+const ARCH_WIDTH: usize = 8;
+
+/// The number of steps executed by a `continue` between two checks for an interrupt.
+const INTERRUPT_CHECK_STEPS: usize = 1024;
+
+const INTERRUPT: u8 = 0x03;
+
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.reustmann.core">
+    <reg name="pc" bitsize="32" type="code_ptr" regnum="0"/>
+    <reg name="sp" bitsize="32" type="data_ptr"/>
+    <reg name="nz" bitsize="32" type="int"/>
+  </feature>
+</target>
+"#;
+
+/// What the client sent.
+#[derive(Debug, PartialEq, Eq)]
+enum Request {
+    Packet(String),
+    Interrupt,
+}
+
+/// Why the program stopped, as given to the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StopReason {
+    Trap,
+    Interrupted,
+    Halted,
+}
+
+impl StopReason {
+    fn reply(self) -> &'static str {
+        match self {
+            StopReason::Trap => "S05",
+            StopReason::Interrupted => "S02",
+            StopReason::Halted => "W00",
+        }
+    }
+}
+
+fn checksum(data: &str) -> u8 {
+    data.bytes().fold(0, |sum, b| sum.wrapping_add(b))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None
+    }
+    (0..hex.len()).step_by(2).map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok())).collect()
+}
+
+fn parse_hex(hex: &str) -> Option<usize> {
+    usize::from_str_radix(hex, 16).ok()
+}
+
+/// Parse the `addr,length` of the memory packets.
+fn parse_range(s: &str) -> Option<(usize, usize)> {
+    let (addr, len) = s.split_once(',')?;
+    Some((parse_hex(addr)?, parse_hex(len)?))
+}
+
+/// A connection to a client.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    last_sent: String,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Connection> {
+        stream.set_nodelay(true)?;
+        let writer = stream.try_clone()?;
+        Ok(Connection { reader: BufReader::new(stream), writer, last_sent: String::new() })
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.reader.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Read the next request, `None` once the client is gone.
+    fn read_request(&mut self) -> io::Result<Option<Request>> {
+        loop {
+            match self.read_byte()? {
+                None => return Ok(None),
+                Some(b'+') => (),
+                Some(b'-') => {
+                    let packet = self.last_sent.clone();
+                    self.send_raw(&packet)?;
+                },
+                Some(INTERRUPT) => return Ok(Some(Request::Interrupt)),
+                Some(b'$') => {
+                    let mut data = Vec::new();
+                    self.reader.read_until(b'#', &mut data)?;
+                    if data.pop() != Some(b'#') {
+                        return Ok(None)
+                    }
+                    let mut sum = [0; 2];
+                    self.reader.read_exact(&mut sum)?;
+                    let data = String::from_utf8_lossy(&data).into_owned();
+                    let expected = std::str::from_utf8(&sum).ok().and_then(|sum| u8::from_str_radix(sum, 16).ok());
+                    if expected == Some(checksum(&data)) {
+                        self.writer.write_all(b"+")?;
+                        return Ok(Some(Request::Packet(data)))
+                    }
+                    self.writer.write_all(b"-")?;
+                },
+                Some(_) => (),
+            }
+        }
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum(data));
+        self.send_raw(&packet)?;
+        self.last_sent = packet;
+        Ok(())
+    }
+
+    fn send_raw(&mut self, packet: &str) -> io::Result<()> {
+        self.writer.write_all(packet.as_bytes())?;
+        self.writer.flush()
+    }
+
+    /// Did the client send an interrupt, without waiting for it.
+    fn interrupted(&mut self) -> io::Result<bool> {
+        if self.reader.buffer().is_empty() {
+            self.reader.get_ref().set_nonblocking(true)?;
+            let filled = self.reader.fill_buf().map(|buf| buf.len());
+            self.reader.get_ref().set_nonblocking(false)?;
+            match filled {
+                Ok(0) => return Ok(true),
+                Ok(_) => (),
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+                Err(err) => return Err(err),
+            }
+        }
+        if self.reader.buffer()[0] == INTERRUPT {
+            self.reader.consume(1);
+            return Ok(true)
+        }
+        Ok(false)
+    }
+}
+
+/// The program debugged and its breakpoints.
+struct Stub {
+    interpreter: Interpreter,
+    breakpoints: BTreeSet<usize>,
+    halted: bool,
+}
+
+impl Stub {
+    fn word_bytes(&self) -> usize {
+        self.interpreter.arch_width().div_ceil(8)
+    }
+
+    /// The word index of a byte address, if it starts a word in memory.
+    fn word_index(&self, addr: usize) -> Option<usize> {
+        let index = addr / self.word_bytes();
+        if addr.is_multiple_of(self.word_bytes()) && index < self.interpreter.arch_length() { Some(index) } else { None }
+    }
+
+    fn registers(&self) -> [u32; 3] {
+        let bytes = self.word_bytes();
+        [(self.interpreter.pc() * bytes) as u32, (self.interpreter.sp() * bytes) as u32, self.interpreter.nz() as u32]
+    }
+
+    fn set_register(&mut self, number: usize, value: u32) -> bool {
+        let value = value as usize;
+        match number {
+            0 | 1 => match self.word_index(value) {
+                Some(index) if number == 0 => self.interpreter.set_pc(index),
+                Some(index) => self.interpreter.set_sp(index),
+                None => return false,
+            },
+            2 => self.interpreter.set_nz(value != 0),
+            _ => return false,
+        }
+        self.update_halted();
+        true
+    }
+
+    fn memory_bytes(&self) -> Vec<u8> {
+        let bytes = self.word_bytes();
+        self.interpreter.memory().iter().flat_map(|word| word.to_le_bytes()[..bytes].to_vec()).collect()
+    }
+
+    fn read_memory(&self, addr: usize, len: usize) -> Option<Vec<u8>> {
+        let memory = self.memory_bytes();
+        memory.get(addr..addr.checked_add(len)?).map(|bytes| bytes.to_vec())
+    }
+
+    fn write_memory(&mut self, addr: usize, data: &[u8]) -> bool {
+        let mut memory = self.memory_bytes();
+        match addr.checked_add(data.len()) {
+            Some(end) if end <= memory.len() => memory[addr..end].copy_from_slice(data),
+            _ => return false,
+        }
+        let bytes = self.word_bytes();
+        let first = addr / bytes;
+        let last = (addr + data.len()).div_ceil(bytes);
+        for index in first..last {
+            let mut word = [0; 4];
+            word[..bytes].copy_from_slice(&memory[index * bytes..(index + 1) * bytes]);
+            self.interpreter.poke(index, Word::from_le_bytes(word));
+        }
+        self.update_halted();
+        true
+    }
+
+    /// The machine stays halted until the client moves the PC off the `HALT` or overwrites it.
+    fn update_halted(&mut self) {
+        let pc = self.interpreter.pc();
+        self.halted &= decode(self.interpreter.memory()[pc]) == op_codes::HALT;
+    }
+
+    fn step(&mut self) -> StopReason {
+        let Statement(op_code, _) = self.interpreter.step(&mut io::stdin(), &mut io::stdout());
+        if op_code == op_codes::HALT {
+            self.halted = true;
+            return StopReason::Halted
+        }
+        StopReason::Trap
+    }
+
+    /// Execute until a breakpoint, a `HALT` or an interrupt of the client,
+    /// a breakpoint on the instruction executed first is ignored.
+    fn resume(&mut self, connection: &mut Connection) -> io::Result<StopReason> {
+        let mut executed: usize = 0;
+        loop {
+            if executed != 0 && self.breakpoints.contains(&self.interpreter.pc()) {
+                return Ok(StopReason::Trap)
+            }
+            if self.step() == StopReason::Halted {
+                return Ok(StopReason::Halted)
+            }
+            executed += 1;
+            if executed.is_multiple_of(INTERRUPT_CHECK_STEPS) && connection.interrupted()? {
+                return Ok(StopReason::Interrupted)
+            }
+        }
+    }
+
+    /// Answer a packet, `None` when the session is over.
+    fn handle(&mut self, packet: &str, connection: &mut Connection) -> io::Result<Option<String>> {
+        let (kind, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match kind {
+            "?" => self.stop_reply(StopReason::Trap).to_string(),
+            "g" => self.registers().iter().map(|value| encode_hex(&value.to_le_bytes())).collect(),
+            "G" => match decode_hex(args) {
+                Some(ref bytes) if bytes.len() == 12 => {
+                    let values: Vec<_> = bytes.chunks(4)
+                        .map(|value| u32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+                        .collect();
+                    // check the PC and the SP before writing any register
+                    if values[..2].iter().all(|&value| self.word_index(value as usize).is_some()) {
+                        for (number, &value) in values.iter().enumerate() {
+                            self.set_register(number, value);
+                        }
+                        "OK"
+                    } else { "E01" }.to_string()
+                },
+                _ => "E01".to_string(),
+            },
+            "p" => match parse_hex(args).and_then(|number| self.registers().get(number).cloned()) {
+                Some(value) => encode_hex(&value.to_le_bytes()),
+                None => "E01".to_string(),
+            },
+            "P" => {
+                let register = args.split_once('=').and_then(|(number, value)| {
+                    let value = decode_hex(value).filter(|bytes| bytes.len() == 4)?;
+                    Some((parse_hex(number)?, u32::from_le_bytes([value[0], value[1], value[2], value[3]])))
+                });
+                match register {
+                    Some((number, value)) if self.set_register(number, value) => "OK".to_string(),
+                    _ => "E01".to_string(),
+                }
+            },
+            "m" => match parse_range(args).and_then(|(addr, len)| self.read_memory(addr, len)) {
+                Some(bytes) => encode_hex(&bytes),
+                None => "E01".to_string(),
+            },
+            "M" => {
+                let write = args.split_once(':').and_then(|(range, data)| Some((parse_range(range)?, decode_hex(data)?)));
+                match write {
+                    Some(((addr, len), data)) if data.len() == len && self.write_memory(addr, &data) => "OK".to_string(),
+                    _ => "E01".to_string(),
+                }
+            },
+            "s" | "c" if self.halted => StopReason::Halted.reply().to_string(),
+            "s" => self.step().reply().to_string(),
+            "c" => self.resume(connection)?.reply().to_string(),
+            "Z" | "z" => {
+                let mut fields = args.split(',');
+                match (fields.next(), fields.next().and_then(parse_hex)) {
+                    (Some("0"), Some(addr)) => match self.word_index(addr) {
+                        Some(index) => {
+                            if kind == "Z" { self.breakpoints.insert(index); } else { self.breakpoints.remove(&index); }
+                            "OK".to_string()
+                        },
+                        None => "E01".to_string(),
+                    },
+                    (Some("0"), None) => "E01".to_string(),
+                    _ => String::new(),
+                }
+            },
+            "H" => "OK".to_string(),
+            "k" => return Ok(None),
+            "D" => {
+                connection.send("OK")?;
+                return Ok(None)
+            },
+            "q" => self.query(args),
+            _ => String::new(),
+        };
+        Ok(Some(reply))
+    }
+
+    fn stop_reply(&self, reason: StopReason) -> &'static str {
+        if self.halted { StopReason::Halted.reply() } else { reason.reply() }
+    }
+
+    fn query(&self, query: &str) -> String {
+        if query.starts_with("Supported") {
+            return "PacketSize=4000;qXfer:features:read+".to_string()
+        }
+        if let Some(range) = query.strip_prefix("Xfer:features:read:target.xml:") {
+            return match parse_range(range) {
+                Some((offset, len)) if offset < TARGET_XML.len() => {
+                    let end = TARGET_XML.len().min(offset + len);
+                    let prefix = if end == TARGET_XML.len() { "l" } else { "m" };
+                    format!("{}{}", prefix, &TARGET_XML[offset..end])
+                },
+                Some(_) => "l".to_string(),
+                None => "E01".to_string(),
+            }
+        }
+        match query {
+            "C" => "QC1".to_string(),
+            "Attached" => "1".to_string(),
+            "fThreadInfo" => "m1".to_string(),
+            "sThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+fn serve(stub: &mut Stub, stream: TcpStream) -> io::Result<()> {
+    let mut connection = Connection::new(stream)?;
+    while let Some(request) = connection.read_request()? {
+        let packet = match request {
+            Request::Packet(packet) => packet,
+            Request::Interrupt => {
+                let reply = stub.stop_reply(StopReason::Interrupted);
+                connection.send(reply)?;
+                continue
+            },
+        };
+        let reply = stub.handle(&packet, &mut connection)?;
+        io::stdout().flush()?;
+        match reply {
+            Some(reply) => connection.send(&reply)?,
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Run the `gdbserver <file> [--port N] [--dialect D] [--encoding E]` subcommand,
+/// return the exit status of the process.
+pub fn run<I: Iterator<Item=String>>(mut args: I) -> i32 {
+    let usage = "usage: reustmann gdbserver <file> [--port N] [--dialect D] [--encoding E]";
+    let file_name = match args.next() {
+        Some(file_name) => file_name,
+        None => {
+            eprintln!("{}", usage);
+            return 2
+        },
+    };
+
+    let mut port = DEFAULT_PORT;
+    let mut dialect = Dialect::default();
+    let mut encoding = Encoding::default();
+    while let Some(arg) = args.next() {
+        let value = match args.next() {
+            Some(value) => value,
+            None => {
+                eprintln!("{}", usage);
+                return 2
+            },
+        };
+        let parsed = match arg.as_str() {
+            "--port" => value.parse().map(|value| port = value).map_err(|_| format!("invalid port {:?}", value)),
+            "--dialect" => value.parse().map(|value| dialect = value),
+            "--encoding" => value.parse().map(|value| encoding = value),
+            _ => Err(usage.to_string()),
+        };
+        if let Err(err) = parsed {
+            eprintln!("{}", err);
+            return 2
+        }
+    }
+
+    let mut source = match fs::read(&file_name) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", file_name, err);
+            return 2
+        },
+    };
+    if source.last() == Some(&b'\n') {
+        source.pop();
+    }
+    let program = Program::from_iter(source);
+    let mut interpreter = match Interpreter::new(program.memory().len(), ARCH_WIDTH) {
+        Ok(interpreter) => interpreter,
+        Err(err) => {
+            eprintln!("{}: {}", file_name, err);
+            return 2
+        },
+    };
+    interpreter.copy_program(&program);
+    interpreter.set_dialect(dialect);
+    interpreter.set_encoding(encoding);
+
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("cannot listen on port {}: {}", port, err);
+            return 2
+        },
+    };
+    match listener.local_addr() {
+        Ok(addr) => eprintln!("Listening on {}", addr),
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2
+        },
+    }
+
+    let mut stub = Stub { interpreter, breakpoints: BTreeSet::new(), halted: false };
+    let result = listener.accept().and_then(|(stream, _)| serve(&mut stub, stream));
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        },
+    }
+}
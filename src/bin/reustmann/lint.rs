@@ -0,0 +1,60 @@
+use std::fs;
+
+use reustmann::Program;
+use reustmann::analysis::lint;
+
+/// The arch width of the machine linted for when none is given.
+const DEFAULT_ARCH_WIDTH: usize = 8;
+
+/// Run the `lint <file> [arch_length [arch_width]]` subcommand,
+/// return the exit status of the process.
+pub fn run<I: Iterator<Item=String>>(mut args: I) -> i32 {
+    let file_name = match args.next() {
+        Some(file_name) => file_name,
+        None => {
+            eprintln!("usage: reustmann lint <file> [arch_length [arch_width]]");
+            return 2
+        },
+    };
+
+    let program = match fs::read(&file_name) {
+        Ok(mut source) => {
+            if source.last() == Some(&b'\n') {
+                source.pop();
+            }
+            Program::from_iter(source)
+        },
+        Err(err) => {
+            eprintln!("{}: {}", file_name, err);
+            return 2
+        },
+    };
+
+    let arch_length = match args.next().map(|s| s.parse::<usize>()) {
+        Some(Ok(value)) => value,
+        Some(Err(err)) => {
+            eprintln!("invalid arch length: {}", err);
+            return 2
+        },
+        None => program.memory().len(),
+    };
+
+    let arch_width = match args.next().map(|s| s.parse::<usize>()) {
+        Some(Ok(value)) if (6..=32).contains(&value) => value,
+        Some(Ok(value)) => {
+            eprintln!("invalid arch width: {} is not in the range [6..32]", value);
+            return 2
+        },
+        Some(Err(err)) => {
+            eprintln!("invalid arch width: {}", err);
+            return 2
+        },
+        None => DEFAULT_ARCH_WIDTH,
+    };
+
+    let lints = lint(&program, arch_length, arch_width);
+    for lint in &lints {
+        println!("{}:{}", file_name, lint);
+    }
+    if lints.is_empty() { 0 } else { 1 }
+}
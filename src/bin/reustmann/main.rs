@@ -2,42 +2,87 @@
 extern crate rustyline;
 extern crate reustmann;
 
+#[macro_use] mod term;
+mod breakpoint;
+mod expr;
 mod command;
 mod debugger;
 mod debugger_error;
-mod sink_debug;
 mod display;
+mod gdbserver;
+mod lint;
+mod script;
+mod session;
 
 use rustyline::completion::FilenameCompleter;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
+use std::io::IsTerminal;
+
 use command::Command;
-use debugger::Debugger;
+use debugger::{Debugger, Flow};
+use debugger_error::display_debugger_error;
 
 fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut dbg = Debugger::new();
+    let mut first = true;
+    let mut script = None;
+    let mut commands = Vec::new();
+    while let Some(arg) = args.next() {
+        let command = match arg.as_str() {
+            "lint" if first => std::process::exit(lint::run(args)),
+            "gdbserver" if first => std::process::exit(gdbserver::run(args)),
+            "--script" => {
+                first = false;
+                match args.next() {
+                    Some(file_name) => { script = Some(file_name); continue },
+                    None => None,
+                }
+            },
+            "--encoding" => args.next().map(|s| s.parse().map(|e| Command::Encoding(Some(e)))),
+            "--dialect" => args.next().map(|s| s.parse().map(|d| Command::Dialect(Some(d)))),
+            _ => { printlnc!(red: "invalid argument {:?}", arg); std::process::exit(2) },
+        };
+        match command {
+            Some(Ok(command)) => commands.push(command),
+            Some(Err(e)) => { printlnc!(red: "{}", e); std::process::exit(2) },
+            None => { printlnc!(red: "missing value for {}", arg); std::process::exit(2) },
+        }
+        first = false;
+    }
+
+    // piped commands are run as a script
+    if script.is_none() && !std::io::stdin().is_terminal() {
+        script = Some("-".into());
+    }
+    if script.is_some() {
+        term::set_colors(false);
+    }
+    for command in &commands {
+        if let Err(err) = dbg.execute(command) {
+            display_debugger_error(&err);
+            std::process::exit(2)
+        }
+    }
+    if let Some(script) = script {
+        std::process::exit(script::run(&mut dbg, &script))
+    }
+
     let file_comp = FilenameCompleter::new();
     let mut rustyline = Editor::new();
 
     rustyline.set_completer(Some(&file_comp));
-    if let Err(_) = rustyline.load_history("history.txt") {
+    if rustyline.load_history("history.txt").is_err() {
         printlnc!(yellow: "No previous history.");
     }
 
-    let mut last_command = None;
-    let mut dbg = Debugger::new();
-
     // FIXME do this elsewhere
     // if let Ok(ref interpreter) = dbg.interpreter() {
     //     display::display_interpreter_properties(interpreter);
     // }
 
-    // let mut input = std::io::empty();
-    let mut input = "\nHello".as_bytes();
-
-    // let mut output = sink_debug::sink_debug();
-    let mut output = Vec::<u8>::new();
-
     loop {
         let prompt = format!(colorify!(dark_grey: "({}) "), "rmdb");
         let readline = rustyline.readline(&prompt);
@@ -45,31 +90,22 @@ fn main() {
             Ok(line) => {
                 rustyline.add_history_entry(&line);
 
-                let command = match (line.parse(), last_command) {
-                    (Ok(Command::Repeat), Some(c)) => Ok(c),
-                    (Ok(Command::Repeat), None) => Err("No last command.".into()),
-                    (Ok(c), _) => Ok(c),
-                    (Err(e), _) => Err(e),
-                };
-
-                match command {
-                    Ok(Command::Exit) => break,
-                    Ok(Command::Repeat) => unreachable!(),
-                    Ok(ref command) => dbg.execute(&command, &mut input, &mut output), // FIXME retrieve error
-                    Err(ref e) => printlnc!(red: "{}", e),
+                match dbg.execute_line(&line) {
+                    Ok(Flow::Exit) => break,
+                    Ok(Flow::Continue) => (),
+                    Err(err) => display_debugger_error(&err),
                 }
-                last_command = command.ok();
             },
             Err(ReadlineError::Interrupted) => {
-                println!("CTRL-C");
+                outln!("CTRL-C");
                 break
             },
             Err(ReadlineError::Eof) => {
-                println!("CTRL-D");
+                outln!("CTRL-D");
                 break
             },
             Err(err) => {
-                println!("Error: {:?}", err);
+                outln!("Error: {:?}", err);
                 break
             }
         }
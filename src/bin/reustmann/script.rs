@@ -0,0 +1,31 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use debugger::Debugger;
+
+/// Run the debugger commands of a file, `-` for the standard input,
+/// return the exit status of the process.
+///
+/// Blank lines and lines starting with `#` are ignored, the script stops
+/// on the first error or failed `assert` and reports it as `file:line: message`.
+pub fn run(dbg: &mut Debugger, file_name: &str) -> i32 {
+    let reader: Box<dyn BufRead> = if file_name == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        match File::open(file_name) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("{}: {}", file_name, err);
+                return 2
+            },
+        }
+    };
+
+    match dbg.execute_lines(file_name, reader) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        },
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::str::FromStr;
+
+use reustmann::{Dialect, Encoding, Word};
+use reustmann::corpus::{quote, unquote};
+
+/// The state of the debugger written by `save-session`, one `key: value` per line:
+///
+/// ```text
+/// program: misc/hello_world.rm
+/// rank: 30 8
+/// encoding: byte
+/// dialect: reustmann
+/// pc: 16
+/// sp: 0
+/// nz: true
+/// cycles: 16
+/// memory: 0a 05 10 10 04 ...
+/// input: ""
+/// input_position: 0
+/// output: "Hello World!"
+/// output_length: 12
+/// breakpoint: break op O if out > 3
+/// ```
+///
+/// `dialect` is the name of a preset followed by the choices that differ from it
+/// (see `Dialect::spec`), `breakpoint` lines are the commands creating the breakpoints again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub program: Option<String>,
+    pub arch_length: usize,
+    pub arch_width: usize,
+    pub encoding: Encoding,
+    pub dialect: Dialect,
+    pub pc: usize,
+    pub sp: usize,
+    pub nz: bool,
+    pub cycles: usize,
+    pub memory: Vec<Word>,
+    pub input: Vec<u8>,
+    pub input_position: usize,
+    pub output: Vec<u8>,
+    pub output_length: usize,
+    pub breakpoints: Vec<String>,
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# rmdb session")?;
+        if let Some(ref program) = self.program {
+            writeln!(f, "program: {}", program)?;
+        }
+        writeln!(f, "rank: {} {}", self.arch_length, self.arch_width)?;
+        writeln!(f, "encoding: {}", self.encoding)?;
+        writeln!(f, "dialect: {}", self.dialect.spec())?;
+        writeln!(f, "pc: {}", self.pc)?;
+        writeln!(f, "sp: {}", self.sp)?;
+        writeln!(f, "nz: {}", self.nz)?;
+        writeln!(f, "cycles: {}", self.cycles)?;
+        let memory: Vec<_> = self.memory.iter().map(|word| format!("{:02x}", word)).collect();
+        writeln!(f, "memory: {}", memory.join(" "))?;
+        writeln!(f, "input: {}", quote(&self.input))?;
+        writeln!(f, "input_position: {}", self.input_position)?;
+        writeln!(f, "output: {}", quote(&self.output))?;
+        writeln!(f, "output_length: {}", self.output_length)?;
+        for breakpoint in &self.breakpoints {
+            writeln!(f, "breakpoint: {}", breakpoint)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid {} {:?}", key, value))
+}
+
+impl FromStr for Session {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut program = None;
+        let mut rank = None;
+        let mut encoding = Encoding::default();
+        let mut dialect = Dialect::default();
+        let (mut pc, mut sp, mut nz, mut cycles) = (0, 0, false, 0);
+        let mut memory = None;
+        let (mut input, mut input_position) = (Vec::new(), 0);
+        let (mut output, mut output_length) = (Vec::new(), 0);
+        let mut breakpoints = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let error = |msg: String| format!("line {}: {}", i + 1, msg);
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(error(format!("expected `key: value`, found {:?}", line))),
+            };
+            match key {
+                "program" => program = Some(value.to_string()),
+                "rank" => {
+                    let mut values = value.split_whitespace();
+                    let (length, width) = match (values.next(), values.next(), values.next()) {
+                        (Some(length), Some(width), None) => (length, width),
+                        _ => return Err(error(format!("expected `rank: L W`, found {:?}", value))),
+                    };
+                    let length = parse_value("arch length", length).map_err(&error)?;
+                    rank = Some((length, parse_value("arch width", width).map_err(&error)?));
+                },
+                "encoding" => encoding = value.parse().map_err(&error)?,
+                "dialect" => dialect = value.parse().map_err(&error)?,
+                "pc" => pc = parse_value(key, value).map_err(&error)?,
+                "sp" => sp = parse_value(key, value).map_err(&error)?,
+                "nz" => nz = parse_value(key, value).map_err(&error)?,
+                "cycles" => cycles = parse_value(key, value).map_err(&error)?,
+                "memory" => {
+                    let words = value.split_whitespace().map(|word| {
+                        Word::from_str_radix(word, 16).map_err(|_| error(format!("invalid word {:?}", word)))
+                    });
+                    memory = Some(words.collect::<Result<Vec<_>, _>>()?);
+                },
+                "input" => input = unquote(value).map_err(&error)?,
+                "input_position" => input_position = parse_value(key, value).map_err(&error)?,
+                "output" => output = unquote(value).map_err(&error)?,
+                "output_length" => output_length = parse_value(key, value).map_err(&error)?,
+                "breakpoint" => breakpoints.push(value.to_string()),
+                _ => return Err(error(format!("unknown key {:?}", key))),
+            }
+        }
+
+        let (arch_length, arch_width) = rank.ok_or("missing rank")?;
+        let memory = memory.ok_or("missing memory")?;
+        if memory.len() != arch_length {
+            return Err(format!("{} words of memory for an arch length of {}", memory.len(), arch_length))
+        }
+        if pc >= arch_length || sp >= arch_length {
+            return Err(format!("pc {} or sp {} out of the {} words of memory", pc, sp, arch_length))
+        }
+        if input_position > input.len() {
+            return Err(format!("input position {} after the {} bytes of input", input_position, input.len()))
+        }
+        Ok(Session {
+            program, arch_length, arch_width, encoding, dialect, pc, sp, nz, cycles, memory,
+            input, input_position, output, output_length, breakpoints,
+        })
+    }
+}
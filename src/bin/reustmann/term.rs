@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLORS: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable the colors of the lines printed by `outln!` and `printlnc!`,
+/// a script runs without them to get a stable output.
+pub fn set_colors(enabled: bool) {
+    COLORS.store(enabled, Ordering::Relaxed);
+}
+
+/// Remove the color escapes of a string.
+pub fn strip_colors(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+pub fn print_line(line: &str) {
+    if COLORS.load(Ordering::Relaxed) {
+        println!("{}", line);
+    } else {
+        println!("{}", strip_colors(line));
+    }
+}
+
+macro_rules! outln {
+    () => { $crate::term::print_line("") };
+    ($($arg:tt)*) => { $crate::term::print_line(&format!($($arg)*)) };
+}
+
+// shadows the macro of colorify to follow `set_colors`
+macro_rules! printlnc {
+    ($color:ident: $fmt:expr) => { outln!(colorify!($color: $fmt)) };
+    ($color:ident: $fmt:expr, $($arg:tt)*) => { outln!(colorify!($color: $fmt), $($arg)*) };
+}
@@ -0,0 +1,518 @@
+//! A conformance suite derived from the equations documented on each
+//! [`Instruction`](../instruction/enum.Instruction.html).
+//!
+//! Each [`Case`](struct.Case.html) sets up a machine, executes one instruction
+//! and gives the state expected afterward. The cases follow the instruction docs,
+//! they describe the `Dialect::iota()` semantics, [`reustmann_cases`](fn.reustmann_cases.html)
+//! replaces the ones that differ in `Dialect::reustmann()`. Any engine implementing
+//! [`Engine`](trait.Engine.html) can be checked against them:
+//!
+//! ```
+//! use reustmann::{Dialect, Interpreter};
+//! use reustmann::conformance::{self, cases};
+//!
+//! let failures = conformance::check(&cases(), |arch_length, arch_width| {
+//!     let mut interpreter = Interpreter::new(arch_length, arch_width).unwrap();
+//!     interpreter.set_dialect(Dialect::iota());
+//!     interpreter
+//! });
+//! assert!(failures.is_empty());
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+
+use crate::instruction::op_codes::*;
+use crate::interpreter::{ExecutionSucceeded, Interpreter};
+use crate::machine_io::MachineIo;
+use crate::memory::Word;
+
+/// The registers and the memory of a machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineState {
+    pub memory: Vec<Word>,
+    pub pc: usize,
+    pub sp: usize,
+    pub nz: bool,
+}
+
+/// A machine the conformance suite can drive.
+pub trait Engine {
+    /// Replace the registers and the memory,
+    /// the memory has the arch length the engine was created with.
+    fn load_state(&mut self, state: &MachineState);
+
+    /// Execute one instruction, return its execution status.
+    fn step_io(&mut self, io: &mut dyn MachineIo) -> ExecutionSucceeded;
+
+    fn state(&self) -> MachineState;
+}
+
+impl Engine for Interpreter {
+    fn load_state(&mut self, state: &MachineState) {
+        self.memory.copy_from_slice(&state.memory);
+        self.pc = state.pc;
+        self.sp = state.sp;
+        self.nz = state.nz;
+    }
+
+    fn step_io(&mut self, io: &mut dyn MachineIo) -> ExecutionSucceeded {
+        Interpreter::step_io(self, io).1
+    }
+
+    fn state(&self) -> MachineState {
+        MachineState { memory: self.memory.clone(), pc: self.pc, sp: self.sp, nz: self.nz }
+    }
+}
+
+/// One instruction executed from a known state.
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub name: String,
+    /// The instruction executed, `NOP` for the words executed as a NOP.
+    pub op_code: OpCode,
+    pub arch_width: usize,
+    /// The state before the step, the arch length is the length of its memory.
+    pub before: MachineState,
+    /// The words available to `IN`, `None` if reading the input fails.
+    pub input: Option<Vec<Word>>,
+    /// Writing the output fails.
+    pub output_fails: bool,
+    /// The state expected after the step.
+    pub after: MachineState,
+    /// The words expected to be written by `OUT`.
+    pub output: Vec<Word>,
+    /// The execution status expected.
+    pub status: ExecutionSucceeded,
+}
+
+/// A case the engine did not pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub case: String,
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.case, self.message)
+    }
+}
+
+struct CaseIo {
+    input: Option<VecDeque<Word>>,
+    output: Vec<Word>,
+    output_fails: bool,
+}
+
+impl MachineIo for CaseIo {
+    fn read_word(&mut self) -> io::Result<Option<Word>> {
+        match self.input {
+            Some(ref mut input) => Ok(input.pop_front()),
+            None => Err(io::Error::other("failing input")),
+        }
+    }
+
+    fn write_word(&mut self, word: Word) -> io::Result<()> {
+        if self.output_fails {
+            return Err(io::Error::other("failing output"))
+        }
+        self.output.push(word);
+        Ok(())
+    }
+}
+
+/// Run a case on an engine created with its arch length and width.
+pub fn check_case<E: Engine>(engine: &mut E, case: &Case) -> Result<(), Failure> {
+    let fail = |message: String| Err(Failure { case: case.name.clone(), message });
+
+    let mut io = CaseIo {
+        input: case.input.clone().map(VecDeque::from),
+        output: Vec::new(),
+        output_fails: case.output_fails,
+    };
+    engine.load_state(&case.before);
+    let status = engine.step_io(&mut io);
+    let state = engine.state();
+    let expected = &case.after;
+
+    if status != case.status {
+        return fail(format!("status is {}, expected {}", status, case.status))
+    }
+    if state.pc != expected.pc {
+        return fail(format!("PC is {}, expected {}", state.pc, expected.pc))
+    }
+    if state.sp != expected.sp {
+        return fail(format!("SP is {}, expected {}", state.sp, expected.sp))
+    }
+    if state.nz != expected.nz {
+        return fail(format!("NZ is {}, expected {}", state.nz, expected.nz))
+    }
+    if state.memory.len() != expected.memory.len() {
+        return fail(format!("memory length is {}, expected {}", state.memory.len(), expected.memory.len()))
+    }
+    let diff = state.memory.iter().zip(&expected.memory).position(|(a, b)| a != b);
+    if let Some(addr) = diff {
+        return fail(format!("memory at {} is {}, expected {}", addr, state.memory[addr], expected.memory[addr]))
+    }
+    if io.output != case.output {
+        return fail(format!("output is {:?}, expected {:?}", io.output, case.output))
+    }
+    Ok(())
+}
+
+/// Run every case on a new engine created by `new_engine(arch_length, arch_width)`,
+/// return the cases that failed.
+pub fn check<E, F>(cases: &[Case], mut new_engine: F) -> Vec<Failure>
+    where E: Engine, F: FnMut(usize, usize) -> E {
+    cases.iter()
+        .filter_map(|case| {
+            let mut engine = new_engine(case.before.memory.len(), case.arch_width);
+            check_case(&mut engine, case).err()
+        })
+        .collect()
+}
+
+/// Return the cases executing the given op code.
+pub fn cases_for(op_code: OpCode) -> Vec<Case> {
+    cases().into_iter().filter(|case| case.op_code == op_code).collect()
+}
+
+// A case under construction, the memory is NOPs except for the op code at the PC,
+// the state after the step defaults to the next PC with nothing else changed.
+struct Spec {
+    name: String,
+    op_code: OpCode,
+    word: Word,
+    len: usize,
+    width: usize,
+    pc: usize,
+    sp: usize,
+    nz: bool,
+    mem: Vec<(usize, Word)>,
+    input: Option<Vec<Word>>,
+    output_fails: bool,
+    then_pc: Option<usize>,
+    then_sp: Option<usize>,
+    then_nz: Option<bool>,
+    writes: Vec<(usize, Word)>,
+    output: Vec<Word>,
+    status: bool,
+}
+
+fn case(name: &str, op_code: OpCode) -> Spec {
+    Spec {
+        name: name.to_string(),
+        op_code,
+        word: op_code as Word,
+        len: 8,
+        width: 8,
+        pc: 3,
+        sp: 0,
+        nz: false,
+        mem: Vec::new(),
+        input: Some(Vec::new()),
+        output_fails: false,
+        then_pc: None,
+        then_sp: None,
+        then_nz: None,
+        writes: Vec::new(),
+        output: Vec::new(),
+        status: true,
+    }
+}
+
+impl Spec {
+    /// Store another word than the op code at the PC.
+    fn word(mut self, word: Word) -> Spec { self.word = word; self }
+    fn len(mut self, len: usize) -> Spec { self.len = len; self }
+    fn width(mut self, width: usize) -> Spec { self.width = width; self }
+    fn pc(mut self, pc: usize) -> Spec { self.pc = pc; self }
+    fn sp(mut self, sp: usize) -> Spec { self.sp = sp; self }
+    fn nz(mut self, nz: bool) -> Spec { self.nz = nz; self }
+
+    fn mem(mut self, addr: usize, word: Word) -> Spec {
+        self.mem.push((addr, word));
+        self
+    }
+
+    /// Place words from the SP upward, the first one on top.
+    fn stack(mut self, words: &[Word]) -> Spec {
+        for (i, &word) in words.iter().enumerate() {
+            self.mem.push(((self.sp + i) % self.len, word));
+        }
+        self
+    }
+
+    fn input(mut self, words: &[Word]) -> Spec { self.input = Some(words.to_vec()); self }
+    fn failing_input(mut self) -> Spec { self.input = None; self }
+    fn failing_output(mut self) -> Spec { self.output_fails = true; self }
+
+    fn then_pc(mut self, pc: usize) -> Spec { self.then_pc = Some(pc); self }
+    fn then_sp(mut self, sp: usize) -> Spec { self.then_sp = Some(sp); self }
+    fn then_nz(mut self, nz: bool) -> Spec { self.then_nz = Some(nz); self }
+
+    fn writes(mut self, addr: usize, word: Word) -> Spec {
+        self.writes.push((addr, word));
+        self
+    }
+
+    fn outputs(mut self, words: &[Word]) -> Spec { self.output = words.to_vec(); self }
+    fn fails(mut self) -> Spec { self.status = false; self }
+
+    fn build(self) -> Case {
+        let mut memory = vec![NOP as Word; self.len];
+        for &(addr, word) in &self.mem {
+            memory[addr] = word;
+        }
+        memory[self.pc] = self.word;
+
+        let mut after = memory.clone();
+        for &(addr, word) in &self.writes {
+            after[addr] = word;
+        }
+
+        Case {
+            name: self.name,
+            op_code: self.op_code,
+            arch_width: self.width,
+            before: MachineState { memory, pc: self.pc, sp: self.sp, nz: self.nz },
+            input: self.input,
+            output_fails: self.output_fails,
+            after: MachineState {
+                memory: after,
+                pc: self.then_pc.unwrap_or((self.pc + 1) % self.len),
+                sp: self.then_sp.unwrap_or(self.sp),
+                nz: self.then_nz.unwrap_or(self.nz),
+            },
+            output: self.output,
+            status: self.status,
+        }
+    }
+}
+
+// the name of a conditional branch, its op code and when it is taken
+type Compare = (&'static str, OpCode, fn(Word, Word) -> bool);
+
+/// Return every case of the suite, at least one per instruction.
+pub fn cases() -> Vec<Case> {
+    let mut specs = vec![
+        case("NOP", NOP),
+        case("NOP wraps the PC at L - 1", NOP).pc(7).then_pc(0),
+        case("NOP keeps NZ", NOP).nz(true),
+        case("unassigned op code is a NOP", NOP).word(200),
+        case("word larger than an op code is a NOP", NOP).width(16).word(0x1_02),
+
+        case("RESET", RESET).pc(5).sp(3).nz(true).then_pc(0).then_sp(0).then_nz(false),
+
+        case("HALT keeps the PC", HALT).pc(4).then_pc(4),
+
+        case("IN", IN).input(&[65]).then_sp(7).writes(7, 65).then_nz(true),
+        case("IN truncates to W", IN).width(6).input(&[0xff]).then_sp(7).writes(7, 0x3f).then_nz(true),
+        case("IN at end of input pushes 0", IN).sp(4).mem(3, 9).nz(true).then_sp(3).writes(3, 0).then_nz(false),
+        case("IN failing pushes 0", IN).sp(4).mem(3, 9).failing_input().then_sp(3).writes(3, 0).fails(),
+
+        case("OUT", OUT).sp(7).stack(&[66]).outputs(&[66]).then_sp(0).then_nz(true),
+        case("OUT of a zero", OUT).sp(5).nz(true).stack(&[0]).outputs(&[0]).then_sp(6).then_nz(false),
+        case("OUT failing", OUT).sp(5).stack(&[66]).failing_output().then_sp(6).then_nz(true).fails(),
+
+        case("POP", POP).sp(7).stack(&[5]).then_sp(0).then_nz(true),
+        case("POP of a zero", POP).sp(5).nz(true).then_sp(6).then_nz(false),
+
+        case("DUP", DUP).stack(&[9]).then_sp(7).writes(7, 9).then_nz(true),
+        case("DUP of a zero", DUP).sp(6).nz(true).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("PUSHPC", PUSHPC).pc(5).sp(2).then_sp(1).writes(1, 5),
+        case("PUSHPC keeps NZ", PUSHPC).pc(5).sp(2).nz(true).then_sp(1).writes(1, 5),
+        case("PUSHPC truncates to W", PUSHPC).len(100).width(6).pc(70).sp(2).then_sp(1).writes(1, 6),
+
+        case("POPPC", POPPC).sp(6).stack(&[2]).then_pc(2).then_sp(7),
+        case("POPPC modulo L", POPPC).sp(7).stack(&[13]).then_pc(5).then_sp(0),
+
+        case("POPSP", POPSP).sp(6).stack(&[2]).then_sp(2),
+        case("POPSP modulo L", POPSP).sp(6).stack(&[11]).then_sp(3),
+
+        case("SPTGT", SPTGT).pc(2).mem(6, TARGET as Word).then_sp(6),
+        case("SPTGT finds the nearest TARGET", SPTGT).pc(2).mem(4, TARGET as Word).mem(6, TARGET as Word).then_sp(4),
+        case("SPTGT without TARGET", SPTGT).pc(2).sp(5),
+        case("SPTGT does not wrap around", SPTGT).pc(4).sp(5).mem(1, TARGET as Word),
+        case("SPTGT at L - 1", SPTGT).pc(7).sp(5).mem(1, TARGET as Word).then_pc(0),
+
+        case("PUSHNZ", PUSHNZ).nz(true).then_sp(7).writes(7, 1),
+        case("PUSHNZ of false", PUSHNZ).sp(6).mem(5, 9).then_sp(5).writes(5, 0),
+
+        case("SWAP", SWAP).sp(5).stack(&[1, 2]).writes(5, 2).writes(6, 1),
+        case("SWAP wraps the stack at L - 1", SWAP).sp(7).stack(&[1, 2]).writes(7, 2).writes(0, 1),
+        case("SWAP keeps NZ", SWAP).sp(5).nz(true).stack(&[0, 0]),
+
+        case("PUSH0", PUSH0).nz(true).mem(7, 9).then_sp(7).writes(7, 0).then_nz(false),
+
+        case("ADD", ADD).sp(6).stack(&[3, 5]).then_sp(5).writes(5, 8).then_nz(true),
+        case("ADD truncates to W", ADD).sp(6).stack(&[200, 100]).then_sp(5).writes(5, 44).then_nz(true),
+        case("ADD to zero", ADD).width(32).sp(6).nz(true).stack(&[1, 0xffff_ffff]).then_sp(5).writes(5, 0).then_nz(false),
+        case("ADD wraps the stack at L - 1", ADD).sp(7).stack(&[3, 5]).then_sp(6).writes(6, 8).then_nz(true),
+
+        case("SUB", SUB).sp(6).stack(&[3, 5]).then_sp(5).writes(5, 2).then_nz(true),
+        case("SUB truncates to W", SUB).width(6).sp(6).stack(&[5, 3]).then_sp(5).writes(5, 62).then_nz(true),
+        case("SUB to zero", SUB).sp(6).nz(true).stack(&[5, 5]).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("INC", INC).sp(6).stack(&[41]).writes(6, 42).then_nz(true),
+        case("INC truncates to W", INC).sp(6).nz(true).stack(&[255]).writes(6, 0).then_nz(false),
+        case("INC with W = 16", INC).width(16).sp(6).stack(&[255]).writes(6, 256).then_nz(true),
+
+        case("DEC", DEC).sp(6).nz(true).stack(&[1]).writes(6, 0).then_nz(false),
+        case("DEC truncates to W", DEC).width(6).sp(6).stack(&[0]).writes(6, 63).then_nz(true),
+
+        case("MUL", MUL).sp(6).stack(&[6, 7]).then_sp(5).writes(5, 42).then_nz(true),
+        case("MUL truncates to W", MUL).sp(6).nz(true).stack(&[16, 16]).then_sp(5).writes(5, 0).then_nz(false),
+        case("MUL with W = 32", MUL).width(32).sp(6).stack(&[0x1_0000, 0x1_0001]).then_sp(5).writes(5, 0x1_0000).then_nz(true),
+
+        case("DIV", DIV).sp(6).stack(&[2, 7]).writes(7, 3).writes(6, 1).then_nz(true),
+        case("DIV to a zero quotient", DIV).sp(6).nz(true).stack(&[7, 2]).writes(7, 0).writes(6, 2).then_nz(false),
+        case("DIV by zero", DIV).sp(6).stack(&[0, 7]).writes(7, 255).writes(6, 0).then_nz(true),
+        case("DIV by zero with W = 6", DIV).width(6).sp(6).stack(&[0, 7]).writes(7, 63).writes(6, 0).then_nz(true),
+        case("DIV wraps the stack at L - 1", DIV).sp(7).stack(&[2, 7]).writes(0, 3).writes(7, 1).then_nz(true),
+
+        case("XOR", XOR).sp(6).stack(&[0b1100, 0b1010]).then_sp(5).writes(5, 0b0110).then_nz(true),
+        case("XOR to zero", XOR).sp(6).nz(true).stack(&[5, 5]).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("AND", AND).sp(6).stack(&[0b1100, 0b1010]).then_sp(5).writes(5, 0b1000).then_nz(true),
+        case("AND to zero", AND).sp(6).nz(true).stack(&[1, 2]).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("OR", OR).sp(6).stack(&[0b1100, 0b1010]).then_sp(5).writes(5, 0b1110).then_nz(true),
+        case("OR to zero", OR).sp(6).nz(true).stack(&[0, 0]).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("SHL", SHL).sp(6).stack(&[0x41]).writes(6, 0x82).then_nz(true),
+        case("SHL truncates to W", SHL).sp(6).nz(true).stack(&[0x80]).writes(6, 0).then_nz(false),
+        case("SHL with W = 16", SHL).width(16).sp(6).stack(&[0x80]).writes(6, 0x100).then_nz(true),
+
+        case("SHR", SHR).sp(6).stack(&[0x81]).writes(6, 0x40).then_nz(true),
+        case("SHR to zero", SHR).sp(6).nz(true).stack(&[1]).writes(6, 0).then_nz(false),
+
+        case("NOT", NOT).sp(6).stack(&[0x0f]).writes(6, 0xf0).then_nz(true),
+        case("NOT truncates to W", NOT).width(6).sp(6).stack(&[0x0f]).writes(6, 0x30).then_nz(true),
+        case("NOT to zero", NOT).width(32).sp(6).nz(true).stack(&[0xffff_ffff]).writes(6, 0).then_nz(false),
+
+        case("BZ taken", BZ).then_pc(5),
+        case("BZ not taken", BZ).nz(true),
+        case("BZ wraps the PC at L - 1", BZ).pc(7).then_pc(1),
+
+        case("BNZ taken", BNZ).nz(true).then_pc(5),
+        case("BNZ not taken", BNZ),
+        case("BNZ wraps the PC at L - 1", BNZ).pc(6).nz(true).then_pc(0),
+    ];
+
+    let compares: [Compare; 4] = [
+        ("BEQ", BEQ, |a, b| a == b),
+        ("BGT", BGT, |a, b| a > b),
+        ("BLT", BLT, |a, b| a < b),
+        ("BGE", BGE, |a, b| a >= b),
+    ];
+    for &(name, op_code, taken) in &compares {
+        for &(a, b) in &[(1, 2), (2, 2), (2, 1)] {
+            let then_pc = if taken(a, b) { 5 } else { 4 };
+            // `a` is the second stacked word and `b` the top
+            specs.push(case(&format!("{} {} {}", name, a, b), op_code).sp(6).stack(&[b, a]).then_pc(then_pc));
+        }
+        let then_pc = if taken(2, 1) { 5 } else { 4 };
+        specs.push(case(&format!("{} wraps the stack at L - 1", name), op_code).sp(7).stack(&[1, 2]).then_pc(then_pc));
+    }
+
+    specs.extend(vec![
+        case("LOOP", LOOP),
+
+        case("ENDL", ENDL).pc(5).mem(1, LOOP as Word).then_pc(2),
+        case("ENDL finds the nearest LOOP", ENDL).pc(5).mem(1, LOOP as Word).mem(3, LOOP as Word).then_pc(4),
+        case("ENDL without LOOP", ENDL).pc(5),
+        case("ENDL does not search past 0", ENDL).pc(3).mem(6, LOOP as Word),
+        case("ENDL at 0", ENDL).pc(0).mem(6, LOOP as Word).then_pc(1),
+
+        case("BRAN", BRAN).pc(2).mem(5, TARGET as Word).then_pc(6),
+        case("BRAN keeps the SP", BRAN).pc(2).sp(4).mem(5, TARGET as Word).then_pc(6),
+        case("BRAN to a TARGET at L - 1", BRAN).pc(2).mem(7, TARGET as Word).then_pc(0),
+        case("BRAN without TARGET", BRAN).pc(2),
+        case("BRAN does not wrap around", BRAN).pc(4).mem(1, TARGET as Word),
+
+        case("BRAP", BRAP).pc(5).mem(1, TARGET as Word).then_pc(2),
+        case("BRAP finds the nearest TARGET", BRAP).pc(5).mem(1, TARGET as Word).mem(3, TARGET as Word).then_pc(4),
+        case("BRAP without TARGET", BRAP).pc(5),
+        case("BRAP does not wrap around", BRAP).pc(3).mem(6, TARGET as Word),
+        case("BRAP at 0", BRAP).pc(0).mem(6, TARGET as Word).then_pc(1),
+
+        case("TARGET", TARGET),
+    ]);
+
+    let skips = [SKIP1, SKIP2, SKIP3, SKIP4, SKIP5, SKIP6, SKIP7, SKIP8, SKIP9];
+    for (n, &op_code) in (1..).zip(&skips) {
+        specs.push(case(&format!("SKIP{}", n), op_code).then_pc((3 + n + 1) % 8));
+    }
+
+    #[cfg(feature = "extended-isa")]
+    specs.extend(extended_specs());
+
+    specs.into_iter().map(Spec::build).collect()
+}
+
+/// The op codes whose cases differ between `Dialect::iota()` and `Dialect::reustmann()`.
+pub const REUSTMANN_OP_CODES: [OpCode; 4] = [PUSHPC, PUSHNZ, DIV, BRAN];
+
+/// Return the cases of the op codes in [`REUSTMANN_OP_CODES`](constant.REUSTMANN_OP_CODES.html)
+/// following the `Dialect::reustmann()` semantics,
+/// the other cases of [`cases`](fn.cases.html) hold in both dialects.
+pub fn reustmann_cases() -> Vec<Case> {
+    let specs = vec![
+        case("PUSHPC sets NZ", PUSHPC).pc(5).sp(2).then_sp(1).writes(1, 5).then_nz(true),
+        case("PUSHPC of a zero", PUSHPC).pc(0).sp(2).nz(true).then_sp(1).writes(1, 0).then_nz(false),
+        case("PUSHPC truncates to W", PUSHPC).len(100).width(6).pc(70).sp(2).then_sp(1).writes(1, 6).then_nz(true),
+
+        case("PUSHNZ", PUSHNZ).nz(true).then_sp(7).writes(7, 1),
+        case("PUSHNZ of false", PUSHNZ).sp(6).mem(5, 9).then_sp(5).writes(5, 0),
+
+        case("DIV pushes the quotient", DIV).sp(6).stack(&[2, 7]).then_sp(5).writes(5, 3).then_nz(true),
+        case("DIV to a zero quotient", DIV).sp(6).nz(true).stack(&[7, 2]).then_sp(5).writes(5, 0).then_nz(false),
+        case("DIV by zero", DIV).sp(6).stack(&[0, 7]).then_sp(5).writes(5, 255).then_nz(true),
+        case("DIV by zero with W = 6", DIV).width(6).sp(6).stack(&[0, 7]).then_sp(5).writes(5, 63).then_nz(true),
+        case("DIV wraps the stack at L - 1", DIV).sp(7).stack(&[2, 7]).then_sp(6).writes(6, 3).then_nz(true),
+
+        case("BRAN sets the SP", BRAN).pc(2).mem(5, TARGET as Word).then_pc(2).then_sp(5),
+        case("BRAN finds the nearest TARGET", BRAN).pc(2).mem(4, TARGET as Word).mem(6, TARGET as Word)
+            .then_pc(2).then_sp(4),
+        case("BRAN without TARGET", BRAN).pc(2).sp(4),
+        case("BRAN does not wrap around", BRAN).pc(4).sp(6).mem(1, TARGET as Word),
+    ];
+    specs.into_iter().map(Spec::build).collect()
+}
+
+#[cfg(feature = "extended-isa")]
+fn extended_specs() -> Vec<Spec> {
+    let mut specs = Vec::new();
+    let compares: [Compare; 2] = [
+        ("BLE", BLE, |a, b| a <= b),
+        ("BNE", BNE, |a, b| a != b),
+    ];
+    for &(name, op_code, taken) in &compares {
+        for &(a, b) in &[(1, 2), (2, 2), (2, 1)] {
+            let then_pc = if taken(a, b) { 5 } else { 4 };
+            specs.push(case(&format!("{} {} {}", name, a, b), op_code).sp(6).stack(&[b, a]).then_pc(then_pc));
+        }
+    }
+
+    specs.extend(vec![
+        case("BSLT taken", BSLT).sp(5).stack(&[1, 0xff]).then_pc(5),
+        case("BSLT not taken", BSLT).sp(5).stack(&[0xff, 1]),
+        case("BSLT with W = 6", BSLT).width(6).sp(5).stack(&[0x1f, 0x20]).then_pc(5),
+        case("BSGT taken", BSGT).sp(5).stack(&[0xff, 1]).then_pc(5),
+        case("BSGT not taken", BSGT).sp(5).stack(&[1, 0xff]),
+
+        case("ROT", ROT).sp(5).stack(&[3, 2, 1]).writes(5, 1).writes(6, 3).writes(7, 2),
+        case("ROT wraps the stack at L - 1", ROT).sp(7).stack(&[3, 2, 1]).writes(7, 1).writes(0, 3).writes(1, 2),
+
+        case("OVER", OVER).sp(6).stack(&[1, 2]).then_sp(5).writes(5, 2).then_nz(true),
+        case("OVER of a zero", OVER).sp(6).nz(true).stack(&[1, 0]).then_sp(5).writes(5, 0).then_nz(false),
+
+        case("MOD", MOD).sp(6).stack(&[3, 7]).then_sp(5).writes(5, 1).then_nz(true),
+        case("MOD by zero", MOD).sp(6).nz(true).stack(&[0, 7]).then_sp(5).writes(5, 0).then_nz(false),
+    ]);
+    specs
+}
@@ -0,0 +1,273 @@
+//! Check programs against the behavior recorded in sidecar expectation files.
+//!
+//! The expectation of a program is stored next to it, in a file with the same
+//! name followed by `.expect`, e.g. `hello_world.rm.expect`:
+//!
+//! ```text
+//! # a comment
+//! rank: 100 8
+//! input: "Hello\n"
+//! output: "Hello World!"
+//! cycles: 200
+//! halt: true
+//! dialect: reustmann
+//! ```
+//!
+//! - `rank` gives the arch length and the arch width, by default the length of
+//!   the program and `8`.
+//! - `input` is given to the program, `output` is what it must write, both are
+//!   quoted strings of bytes where `\n`, `\r`, `\t`, `\0`, `\\`, `\"` and `\xHH`
+//!   escapes are understood, both are empty by default.
+//! - `cycles` is the maximum number of instructions executed, `1000` by default.
+//! - `halt` tells if the program must execute a `HALT` within the cycles budget or
+//!   run through it, `true` by default.
+//! - `dialect` is the name of the [`Dialect`](../dialect/struct.Dialect.html) used,
+//!   `reustmann` by default.
+//! - `newline: keep` loads the final newline of the program file into memory,
+//!   by default it is dropped.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::dialect::Dialect;
+use crate::instruction::op_codes;
+use crate::interpreter::{Interpreter, Statement};
+use crate::program::Program;
+
+/// The extension appended to a program path to find its expectation.
+pub const EXPECTATION_EXTENSION: &str = "expect";
+
+/// What a program must do, as read from its sidecar file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expectation {
+    /// `None` to use the length of the program.
+    pub arch_length: Option<usize>,
+    pub arch_width: usize,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub cycles: usize,
+    pub halt: bool,
+    pub dialect: Dialect,
+    /// Drop the final newline of the program file.
+    pub trim_newline: bool,
+}
+
+impl Default for Expectation {
+    fn default() -> Self {
+        Expectation {
+            arch_length: None,
+            arch_width: 8,
+            input: Vec::new(),
+            output: Vec::new(),
+            cycles: 1000,
+            halt: true,
+            dialect: Dialect::default(),
+            trim_newline: true,
+        }
+    }
+}
+
+/// Unquote a string written like `input` and `output`.
+pub fn unquote(value: &str) -> Result<Vec<u8>, String> {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err(format!("expected a quoted string, found {}", value))
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('"') => bytes.push(b'"'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) if hex.len() == 2 => bytes.push(byte),
+                    _ => return Err(format!("invalid escape \\x{}", hex)),
+                }
+            },
+            Some(c) => return Err(format!("invalid escape \\{}", c)),
+            None => return Err("unterminated escape".to_string()),
+        }
+    }
+    Ok(bytes)
+}
+
+/// Quote bytes the way `input` and `output` are written.
+pub fn quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for &b in bytes {
+        match b {
+            b'\n' => quoted.push_str("\\n"),
+            b'\r' => quoted.push_str("\\r"),
+            b'\t' => quoted.push_str("\\t"),
+            0 => quoted.push_str("\\0"),
+            b'\\' => quoted.push_str("\\\\"),
+            b'"' => quoted.push_str("\\\""),
+            0x20..=0x7e => quoted.push(b as char),
+            _ => quoted.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid {} {:?}", key, value))
+}
+
+impl FromStr for Expectation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut expectation = Expectation::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let error = |msg: String| format!("line {}: {}", i + 1, msg);
+            let (key, value) = match line.find(':') {
+                Some(colon) => (line[..colon].trim(), line[colon + 1..].trim()),
+                None => return Err(error(format!("expected `key: value`, found {:?}", line))),
+            };
+            match key {
+                "rank" => {
+                    let mut values = value.split_whitespace();
+                    let (length, width) = match (values.next(), values.next(), values.next()) {
+                        (Some(length), Some(width), None) => (length, width),
+                        _ => return Err(error(format!("expected `rank: L W`, found {:?}", value))),
+                    };
+                    expectation.arch_length = Some(parse_value("arch length", length).map_err(&error)?);
+                    expectation.arch_width = parse_value("arch width", width).map_err(&error)?;
+                },
+                "input" => expectation.input = unquote(value).map_err(&error)?,
+                "output" => expectation.output = unquote(value).map_err(&error)?,
+                "cycles" => expectation.cycles = parse_value(key, value).map_err(&error)?,
+                "halt" => expectation.halt = parse_value(key, value).map_err(&error)?,
+                "dialect" => expectation.dialect = value.parse().map_err(&error)?,
+                "newline" => expectation.trim_newline = match value {
+                    "keep" => false,
+                    "drop" => true,
+                    _ => return Err(error(format!("expected `keep` or `drop`, found {:?}", value))),
+                },
+                _ => return Err(error(format!("unknown key {:?}", key))),
+            }
+        }
+        Ok(expectation)
+    }
+}
+
+/// A difference between a program and its expectation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+/// Run the program source as described by the expectation,
+/// return the messages describing how it differs.
+pub fn run(source: &[u8], expectation: &Expectation) -> Vec<String> {
+    let mut source = source;
+    if expectation.trim_newline && source.last() == Some(&b'\n') {
+        source = &source[..source.len() - 1];
+    }
+    let program = Program::from_iter(source.iter().cloned());
+
+    let arch_length = expectation.arch_length.unwrap_or(source.len());
+    let mut interpreter = match Interpreter::new(arch_length, expectation.arch_width) {
+        Ok(interpreter) => interpreter,
+        Err(err) => return vec![err.to_string()],
+    };
+    interpreter.set_dialect(expectation.dialect);
+    interpreter.copy_program(&program);
+
+    let mut input = &expectation.input[..];
+    let mut output = Vec::new();
+    let mut halted_after = None;
+    for cycle in 0..expectation.cycles {
+        if let Statement(op_codes::HALT, _) = interpreter.step(&mut input, &mut output) {
+            halted_after = Some(cycle + 1);
+            break
+        }
+    }
+
+    let mut messages = Vec::new();
+    if output != expectation.output {
+        messages.push(format!("output is {}, expected {}", quote(&output), quote(&expectation.output)));
+    }
+    match (halted_after, expectation.halt) {
+        (None, true) => messages.push(format!("did not halt within {} cycles", expectation.cycles)),
+        (Some(cycles), false) => messages.push(format!("halted after {} cycles", cycles)),
+        _ => (),
+    }
+    messages
+}
+
+/// Return the path of the expectation of a program.
+pub fn expectation_path<P: AsRef<Path>>(program: P) -> PathBuf {
+    let mut path = program.as_ref().as_os_str().to_owned();
+    path.push(".");
+    path.push(EXPECTATION_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// Check a program against its expectation file,
+/// a missing or invalid expectation is reported as a mismatch.
+pub fn check_program<P: AsRef<Path>>(path: P) -> io::Result<Vec<Mismatch>> {
+    let path = path.as_ref();
+    let mismatch = |message: String| Mismatch { path: path.to_path_buf(), message };
+
+    let expectation = match fs::read_to_string(expectation_path(path)) {
+        Ok(expectation) => expectation,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+            return Ok(vec![mismatch("missing expectation file".to_string())])
+        },
+        Err(err) => return Err(err),
+    };
+    let expectation = match expectation.parse() {
+        Ok(expectation) => expectation,
+        Err(err) => return Ok(vec![mismatch(format!("invalid expectation: {}", err))]),
+    };
+
+    let source = fs::read(path)?;
+    Ok(run(&source, &expectation).into_iter().map(mismatch).collect())
+}
+
+/// Check every program of a directory, in name order,
+/// the files with the expectation extension are not programs.
+pub fn check_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Mismatch>> {
+    let mut programs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_expectation = path.extension().is_some_and(|ext| ext == EXPECTATION_EXTENSION);
+        if path.is_file() && !is_expectation {
+            programs.push(path);
+        }
+    }
+    programs.sort();
+
+    let mut mismatches = Vec::new();
+    for program in programs {
+        mismatches.extend(check_program(program)?);
+    }
+    Ok(mismatches)
+}
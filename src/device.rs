@@ -0,0 +1,162 @@
+//! Devices that can be mapped onto a range of the interpreter memory.
+//!
+//! Once mapped with [`Interpreter::map_device`], every read and write
+//! through the SP or the PC in the range is forwarded to the device.
+//! Keep a `Rc<RefCell<D>>` clone of a device to inspect it while it is mapped.
+//!
+//! [`Interpreter::map_device`]: ../struct.Interpreter.html#method.map_device
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crate::memory::Word;
+
+/// A device mapped onto a range of memory locations.
+pub trait Device {
+    /// Read the word at `offset` from the start of the mapped range.
+    fn read(&mut self, offset: usize) -> Word;
+
+    /// Write the word at `offset` from the start of the mapped range.
+    fn write(&mut self, offset: usize, value: Word);
+
+    /// Called once per executed instruction.
+    fn tick(&mut self) {}
+}
+
+impl<D: ?Sized + Device> Device for Rc<RefCell<D>> {
+    fn read(&mut self, offset: usize) -> Word {
+        self.borrow_mut().read(offset)
+    }
+
+    fn write(&mut self, offset: usize, value: Word) {
+        self.borrow_mut().write(offset, value)
+    }
+
+    fn tick(&mut self) {
+        self.borrow_mut().tick()
+    }
+}
+
+/// A port that gives a new pseudo-random word on each read,
+/// writing to it sets the seed.
+#[derive(Debug, Clone)]
+pub struct RandomPort {
+    state: u32,
+}
+
+impl RandomPort {
+    /// Create a random port, the same seed always gives the same sequence.
+    pub fn new(seed: u32) -> RandomPort {
+        RandomPort { state: if seed == 0 { 0x9e37_79b9 } else { seed } }
+    }
+}
+
+impl Device for RandomPort {
+    fn read(&mut self, _offset: usize) -> Word {
+        // xorshift32
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    fn write(&mut self, _offset: usize, value: Word) {
+        *self = RandomPort::new(value);
+    }
+}
+
+/// A register counting the instructions executed, writing to it sets the counter.
+#[derive(Debug, Default, Clone)]
+pub struct CycleCounter {
+    cycles: u64,
+}
+
+impl CycleCounter {
+    pub fn new() -> CycleCounter {
+        CycleCounter::default()
+    }
+
+    /// Return the number of instructions executed since the counter was mapped or set.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+}
+
+impl Device for CycleCounter {
+    fn read(&mut self, _offset: usize) -> Word {
+        self.cycles as Word
+    }
+
+    fn write(&mut self, _offset: usize, value: Word) {
+        self.cycles = value as u64;
+    }
+
+    fn tick(&mut self) {
+        self.cycles += 1;
+    }
+}
+
+/// A `width` x `height` image, one word per pixel, row by row.
+///
+/// A pixel is a grey level in its low 8 bits,
+/// or a `0xRRGGBB` color for a framebuffer created with `Framebuffer::rgb`.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    rgb: bool,
+    pixels: Vec<Word>,
+}
+
+impl Framebuffer {
+    /// Create a black framebuffer of grey levels, map it on `width * height` locations.
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer { width, height, rgb: false, pixels: vec![0; width * height] }
+    }
+
+    /// Create a black framebuffer of `0xRRGGBB` colors,
+    /// words must be at least 24 bits wide to use every color.
+    pub fn rgb(width: usize, height: usize) -> Framebuffer {
+        Framebuffer { rgb: true, ..Framebuffer::new(width, height) }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the pixels row by row.
+    pub fn pixels(&self) -> &[Word] {
+        &self.pixels
+    }
+
+    /// Write the image in the binary PPM format.
+    pub fn write_ppm<W: ?Sized + Write>(&self, output: &mut W) -> io::Result<()> {
+        write!(output, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 3);
+        for &pixel in &self.pixels {
+            if self.rgb {
+                bytes.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8]);
+            } else {
+                bytes.extend_from_slice(&[pixel as u8; 3]);
+            }
+        }
+        output.write_all(&bytes)
+    }
+}
+
+impl Device for Framebuffer {
+    fn read(&mut self, offset: usize) -> Word {
+        self.pixels.get(offset).cloned().unwrap_or(0)
+    }
+
+    fn write(&mut self, offset: usize, value: Word) {
+        if let Some(pixel) = self.pixels.get_mut(offset) {
+            *pixel = value;
+        }
+    }
+}
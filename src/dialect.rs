@@ -0,0 +1,204 @@
+//! Behaviors the specification leaves open or that differ between implementations.
+//!
+//! An [`Interpreter`](../struct.Interpreter.html) runs the `Dialect::reustmann()`
+//! semantics by default, `Dialect::iota()` follows the original Iota machine.
+//! The `Display` of a dialect names every choice it makes,
+//! print it next to results so that they can be reproduced.
+//!
+//! A dialect parses from the name of a preset followed by the choices
+//! that differ from it, `iota,div-by-zero=zero,target-search-wraps=yes`
+//! for example, [`Dialect::spec`](struct.Dialect.html#method.spec)
+//! gives the string that parses back into a dialect.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// What `DIV` leaves on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivResult {
+    /// Push the quotient, the operands stay on the stack.
+    Quotient,
+    /// Replace the operands by the quotient and the remainder, the remainder on top.
+    QuotientRemainder,
+}
+
+/// The quotient of `DIV` when the divisor is zero, the remainder is always zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivByZero {
+    /// The maximum value a word can hold.
+    MaxWord,
+    Zero,
+}
+
+/// The semantics the interpreter gives to the behaviors the spec leaves open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    pub div_result: DivResult,
+    pub div_by_zero: DivByZero,
+    /// `SPTGT`, `BRAN` and `BRAP` continue their search for a `TARGET`
+    /// from the other end of the memory instead of stopping at it.
+    pub target_search_wraps: bool,
+    /// `ENDL` continues its search for a `LOOP` past location 0,
+    /// from location L - 1.
+    pub loop_search_wraps: bool,
+    /// `BRAN` sets the SP to the `TARGET` found, leaving the PC on the `BRAN`,
+    /// instead of branching the PC after it.
+    pub bran_sets_sp: bool,
+    /// `PUSHPC` and `PUSHNZ` set NZ from the word pushed instead of leaving it unchanged.
+    pub push_sets_nz: bool,
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b { "yes" } else { "no" }
+}
+
+fn parse_yes_no(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("invalid {} {:?}, expected yes or no", key, value)),
+    }
+}
+
+impl Dialect {
+    /// The semantics of this crate up to version 0.2, the default.
+    pub fn reustmann() -> Dialect {
+        Dialect {
+            div_result: DivResult::Quotient,
+            div_by_zero: DivByZero::MaxWord,
+            target_search_wraps: false,
+            loop_search_wraps: false,
+            bran_sets_sp: true,
+            push_sets_nz: true,
+        }
+    }
+
+    /// The semantics of the original Iota machine, as described by the instruction docs.
+    pub fn iota() -> Dialect {
+        Dialect {
+            div_result: DivResult::QuotientRemainder,
+            div_by_zero: DivByZero::MaxWord,
+            target_search_wraps: false,
+            loop_search_wraps: false,
+            bran_sets_sp: false,
+            push_sets_nz: false,
+        }
+    }
+
+    /// Return the name of the preset this dialect is equal to, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        if *self == Dialect::reustmann() {
+            Some("reustmann")
+        } else if *self == Dialect::iota() {
+            Some("iota")
+        } else {
+            None
+        }
+    }
+
+    /// Return the choices of this dialect as `(key, value)` pairs,
+    /// in the order they are displayed.
+    fn choices(&self) -> [(&'static str, &'static str); 6] {
+        let div_result = match self.div_result {
+            DivResult::Quotient => "quotient",
+            DivResult::QuotientRemainder => "quotient-remainder",
+        };
+        let div_by_zero = match self.div_by_zero {
+            DivByZero::MaxWord => "max-word",
+            DivByZero::Zero => "zero",
+        };
+        [
+            ("div", div_result),
+            ("div-by-zero", div_by_zero),
+            ("target-search-wraps", yes_no(self.target_search_wraps)),
+            ("loop-search-wraps", yes_no(self.loop_search_wraps)),
+            ("bran-sets-sp", yes_no(self.bran_sets_sp)),
+            ("push-sets-nz", yes_no(self.push_sets_nz)),
+        ]
+    }
+
+    /// Set the choice named `key` from its `value` as written by `spec`.
+    fn set_choice(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "div" => self.div_result = match value {
+                "quotient" => DivResult::Quotient,
+                "quotient-remainder" => DivResult::QuotientRemainder,
+                _ => return Err(format!("invalid div {:?}, expected quotient or quotient-remainder", value)),
+            },
+            "div-by-zero" => self.div_by_zero = match value {
+                "max-word" => DivByZero::MaxWord,
+                "zero" => DivByZero::Zero,
+                _ => return Err(format!("invalid div-by-zero {:?}, expected max-word or zero", value)),
+            },
+            "target-search-wraps" => self.target_search_wraps = parse_yes_no(key, value)?,
+            "loop-search-wraps" => self.loop_search_wraps = parse_yes_no(key, value)?,
+            "bran-sets-sp" => self.bran_sets_sp = parse_yes_no(key, value)?,
+            "push-sets-nz" => self.push_sets_nz = parse_yes_no(key, value)?,
+            _ => return Err(format!("invalid dialect choice {:?}", key)),
+        }
+        Ok(())
+    }
+
+    /// Return the string that parses back into this dialect:
+    /// the name of the closest preset followed by the choices that differ from it.
+    pub fn spec(&self) -> String {
+        let choices = self.choices();
+        let differences = |preset: Dialect| -> Vec<String> {
+            preset.choices().iter().zip(&choices)
+                .filter(|&(a, b)| a != b)
+                .map(|(_, &(key, value))| format!(",{}={}", key, value))
+                .collect()
+        };
+        let (reustmann, iota) = (differences(Dialect::reustmann()), differences(Dialect::iota()));
+        let (name, differences) = if iota.len() < reustmann.len() { ("iota", iota) } else { ("reustmann", reustmann) };
+        format!("{}{}", name, differences.concat())
+    }
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::reustmann()
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let div_result = match self.div_result {
+            DivResult::Quotient => "quotient",
+            DivResult::QuotientRemainder => "quotient and remainder",
+        };
+        let div_by_zero = match self.div_by_zero {
+            DivByZero::MaxWord => "max word",
+            DivByZero::Zero => "zero",
+        };
+        write!(f, "{} (div: {}, div by zero: {}, target search wraps: {}, \
+                   loop search wraps: {}, BRAN sets SP: {}, push sets NZ: {})",
+            self.name().unwrap_or("custom"),
+            div_result,
+            div_by_zero,
+            yes_no(self.target_search_wraps),
+            yes_no(self.loop_search_wraps),
+            yes_no(self.bran_sets_sp),
+            yes_no(self.push_sets_nz))
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let mut dialect = match parts.next() {
+            Some("reustmann") => Dialect::reustmann(),
+            Some("iota") => Dialect::iota(),
+            _ => return Err(format!("invalid dialect {:?}, expected reustmann or iota", s)),
+        };
+        for part in parts {
+            match part.find('=') {
+                Some(i) => dialect.set_choice(&part[..i], &part[i + 1..])?,
+                None => return Err(format!("invalid dialect choice {:?}, expected key=value", part)),
+            }
+        }
+        Ok(dialect)
+    }
+}
@@ -0,0 +1,119 @@
+use std::io;
+
+use crate::interpreter::Interpreter;
+use crate::machine_io::MachineIo;
+use crate::memory::Word;
+use crate::observer::Observer;
+
+/// A handler executed in place of an unassigned op code,
+/// registered with [`Interpreter::register_host_call`].
+///
+/// Closures taking a `&mut HostContext` and returning the execution status
+/// are host calls.
+///
+/// [`Interpreter::register_host_call`]: struct.Interpreter.html#method.register_host_call
+pub trait HostCall {
+    /// Execute the call, return `false` if it failed.
+    ///
+    /// The PC is moved to the next location once the call returns,
+    /// unless the call chose where to resume with `HostContext::set_pc`.
+    fn call(&mut self, context: &mut HostContext) -> bool;
+}
+
+impl<F: FnMut(&mut HostContext) -> bool> HostCall for F {
+    fn call(&mut self, context: &mut HostContext) -> bool {
+        self(context)
+    }
+}
+
+/// The registers, the stack and the I/O of the machine, as seen by a host call.
+///
+/// The words pushed or written are truncated to the machine width,
+/// memory accesses go through the mapped devices, the memory watcher
+/// and the observer like the ones of the instructions.
+pub struct HostContext<'a> {
+    pub(crate) interpreter: &'a mut Interpreter,
+    pub(crate) io: &'a mut dyn MachineIo,
+    pub(crate) observer: &'a mut dyn Observer,
+    pub(crate) resume_at: Option<usize>,
+}
+
+impl<'a> HostContext<'a> {
+    /// Return the address of the op code being executed.
+    pub fn pc(&self) -> usize {
+        self.interpreter.pc
+    }
+
+    /// Resume the execution at `pc` modulo L once the call returns,
+    /// instead of the location following the op code.
+    pub fn set_pc(&mut self, pc: usize) {
+        self.resume_at = Some(pc % self.interpreter.arch_length());
+    }
+
+    pub fn sp(&self) -> usize {
+        self.interpreter.sp
+    }
+
+    pub fn set_sp(&mut self, sp: usize) {
+        self.interpreter.sp = sp % self.interpreter.arch_length();
+    }
+
+    pub fn nz(&self) -> bool {
+        self.interpreter.nz
+    }
+
+    pub fn set_nz(&mut self, nz: bool) {
+        self.interpreter.nz = nz;
+    }
+
+    pub fn arch_length(&self) -> usize {
+        self.interpreter.arch_length()
+    }
+
+    pub fn arch_width(&self) -> usize {
+        self.interpreter.arch_width()
+    }
+
+    /// Read the word at `addr` modulo L.
+    pub fn read(&mut self, addr: usize) -> Word {
+        let addr = addr % self.interpreter.arch_length();
+        self.interpreter.read(addr, self.observer)
+    }
+
+    /// Write a word at `addr` modulo L.
+    pub fn write(&mut self, addr: usize, val: Word) {
+        let addr = addr % self.interpreter.arch_length();
+        let val = self.interpreter.trunc(val);
+        self.interpreter.write(addr, val, self.observer)
+    }
+
+    /// Return the word on top of the stack, without popping it.
+    pub fn peek(&mut self) -> Word {
+        let sp = self.interpreter.sp;
+        self.interpreter.read(sp, self.observer)
+    }
+
+    /// Pop the word on top of the stack.
+    pub fn pop(&mut self) -> Word {
+        let val = self.peek();
+        self.interpreter.increment_sp();
+        val
+    }
+
+    /// Push a word onto the stack.
+    pub fn push(&mut self, val: Word) {
+        self.interpreter.decrement_sp();
+        let sp = self.interpreter.sp;
+        self.write(sp, val);
+    }
+
+    /// Read the next word from the input of the machine, `None` at end of input.
+    pub fn read_word(&mut self) -> io::Result<Option<Word>> {
+        self.io.read_word()
+    }
+
+    /// Write a word to the output of the machine.
+    pub fn write_word(&mut self, word: Word) -> io::Result<()> {
+        self.io.write_word(word)
+    }
+}
@@ -0,0 +1,182 @@
+use crate::dialect::{Dialect, DivResult};
+use super::{Instruction, Mnemonic, LongMnemonic, OpCode};
+use super::{mnemonics, long_mnemonics, op_codes};
+use super::Instruction::*;
+
+/// How an instruction changes the PC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// The PC goes to the next location.
+    Fallthrough,
+    /// The PC unconditionally skips over the next `n` locations.
+    Skip(usize),
+    /// The PC skips over the next location if a condition holds.
+    ConditionalSkip,
+    /// The PC goes back after the preceding `LOOP`.
+    LoopBack,
+    /// The PC goes after the nearest `TARGET`.
+    TargetSearch,
+    /// The PC is popped from the stack.
+    Indirect,
+    /// The PC goes back to location 0.
+    Reset,
+    /// The execution stops.
+    Halt,
+}
+
+/// What an instruction does, as described by its documentation.
+///
+/// The documentation is the one of the Iota machine, use
+/// [`effects`](#method.effects) to get the stack and NZ effects
+/// of the instruction in another dialect.
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionInfo {
+    pub instruction: Instruction,
+    pub op_code: OpCode,
+    pub mnemonic: Mnemonic,
+    pub long_mnemonic: LongMnemonic,
+    /// Number of words popped from the stack.
+    pub pops: u8,
+    /// Number of words pushed onto the stack,
+    /// `DIV` pushes its quotient in the reustmann dialect.
+    pub pushes: u8,
+    /// The instruction reads memory through the SP.
+    pub reads_memory: bool,
+    /// The instruction writes memory through the SP.
+    pub writes_memory: bool,
+    /// The instruction updates the NZ flag,
+    /// `PUSHPC` and `PUSHNZ` also do in the reustmann dialect.
+    pub sets_nz: bool,
+    pub control_flow: ControlFlow,
+}
+
+/// The stack and NZ effects of an instruction in a given dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effects {
+    /// Number of words popped from the stack.
+    pub pops: u8,
+    /// Number of words pushed onto the stack.
+    pub pushes: u8,
+    /// The instruction updates the NZ flag.
+    pub sets_nz: bool,
+}
+
+macro_rules! info {
+    ($instr:ident, $name:ident, pops: $pops:expr, pushes: $pushes:expr,
+     reads: $reads:expr, writes: $writes:expr, nz: $nz:expr, flow: $flow:expr) => {
+        InstructionInfo {
+            instruction: $instr,
+            op_code: op_codes::$name,
+            mnemonic: mnemonics::$name,
+            long_mnemonic: long_mnemonics::$name,
+            pops: $pops,
+            pushes: $pushes,
+            reads_memory: $reads,
+            writes_memory: $writes,
+            sets_nz: $nz,
+            control_flow: $flow,
+        }
+    }
+}
+
+use self::ControlFlow::{Fallthrough, ConditionalSkip, LoopBack, TargetSearch, Indirect};
+
+macro_rules! instructions {
+    ($($extra:expr),*) => { [
+        info!(Nop,    NOP,    pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: Fallthrough),
+        info!(Reset,  RESET,  pops: 0, pushes: 0, reads: false, writes: false, nz: true,  flow: ControlFlow::Reset),
+        info!(Halt,   HALT,   pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Halt),
+        info!(In,     IN,     pops: 0, pushes: 1, reads: false, writes: true,  nz: true,  flow: Fallthrough),
+        info!(Out,    OUT,    pops: 1, pushes: 0, reads: true,  writes: false, nz: true,  flow: Fallthrough),
+        info!(Pop,    POP,    pops: 1, pushes: 0, reads: true,  writes: false, nz: true,  flow: Fallthrough),
+        info!(Dup,    DUP,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(PushPc, PUSHPC, pops: 0, pushes: 1, reads: false, writes: true,  nz: false, flow: Fallthrough),
+        info!(PopPc,  POPPC,  pops: 1, pushes: 0, reads: true,  writes: false, nz: false, flow: Indirect),
+        info!(PopSp,  POPSP,  pops: 1, pushes: 0, reads: true,  writes: false, nz: false, flow: Fallthrough),
+        info!(SpTgt,  SPTGT,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: Fallthrough),
+        info!(PushNz, PUSHNZ, pops: 0, pushes: 1, reads: false, writes: true,  nz: false, flow: Fallthrough),
+        info!(Swap,   SWAP,   pops: 0, pushes: 0, reads: true,  writes: true,  nz: false, flow: Fallthrough),
+        info!(Push0,  PUSH0,  pops: 0, pushes: 1, reads: false, writes: true,  nz: true,  flow: Fallthrough),
+        info!(Add,    ADD,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Sub,    SUB,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Inc,    INC,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Dec,    DEC,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Mul,    MUL,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Div,    DIV,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Xor,    XOR,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(And,    AND,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Or,     OR,     pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Shl,    SHL,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Shr,    SHR,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Not,    NOT,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+        info!(Bz,     BZ,     pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ConditionalSkip),
+        info!(Bnz,    BNZ,    pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ConditionalSkip),
+        info!(Beq,    BEQ,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+        info!(Bgt,    BGT,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+        info!(Blt,    BLT,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+        info!(Bge,    BGE,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+        info!(Loop,   LOOP,   pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: Fallthrough),
+        info!(EndL,   ENDL,   pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: LoopBack),
+        info!(BraN,   BRAN,   pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: TargetSearch),
+        info!(BraP,   BRAP,   pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: TargetSearch),
+        info!(Target, TARGET, pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: Fallthrough),
+        info!(Skip1,  SKIP1,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(1)),
+        info!(Skip2,  SKIP2,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(2)),
+        info!(Skip3,  SKIP3,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(3)),
+        info!(Skip4,  SKIP4,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(4)),
+        info!(Skip5,  SKIP5,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(5)),
+        info!(Skip6,  SKIP6,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(6)),
+        info!(Skip7,  SKIP7,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(7)),
+        info!(Skip8,  SKIP8,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(8)),
+        info!(Skip9,  SKIP9,  pops: 0, pushes: 0, reads: false, writes: false, nz: false, flow: ControlFlow::Skip(9)),
+        $($extra,)*
+    ] }
+}
+
+/// The informations of every instruction, indexed by op code.
+#[cfg(not(feature = "extended-isa"))]
+pub const INSTRUCTIONS: [InstructionInfo; 46] = instructions!();
+
+/// The informations of every instruction, indexed by op code.
+#[cfg(feature = "extended-isa")]
+pub const INSTRUCTIONS: [InstructionInfo; 53] = instructions!(
+    info!(Ble,    BLE,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+    info!(Bne,    BNE,    pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+    info!(Rot,    ROT,    pops: 0, pushes: 0, reads: true,  writes: true,  nz: false, flow: Fallthrough),
+    info!(Over,   OVER,   pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+    info!(Mod,    MOD,    pops: 0, pushes: 1, reads: true,  writes: true,  nz: true,  flow: Fallthrough),
+    info!(Bslt,   BSLT,   pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip),
+    info!(Bsgt,   BSGT,   pops: 0, pushes: 0, reads: true,  writes: false, nz: false, flow: ConditionalSkip)
+);
+
+/// Return the informations of an op code,
+/// `None` if the op code is not assigned and executed as a NOP.
+pub fn op_code_info(op_code: OpCode) -> Option<&'static InstructionInfo> {
+    INSTRUCTIONS.get(op_code as usize)
+}
+
+/// Return the informations of a short mnemonic,
+/// `None` if the char is not a mnemonic.
+pub fn mnemonic_info(mnemo: Mnemonic) -> Option<&'static InstructionInfo> {
+    INSTRUCTIONS.iter().find(|info| info.mnemonic == mnemo)
+}
+
+impl InstructionInfo {
+    /// Return the stack and NZ effects of this instruction in the given dialect.
+    pub fn effects(&self, dialect: &Dialect) -> Effects {
+        let mut effects = Effects { pops: self.pops, pushes: self.pushes, sets_nz: self.sets_nz };
+        match self.instruction {
+            PushPc | PushNz => effects.sets_nz = dialect.push_sets_nz,
+            Div if dialect.div_result == DivResult::Quotient => effects.pushes = 1,
+            _ => (),
+        }
+        effects
+    }
+}
+
+impl Instruction {
+    /// Return the informations of this instruction.
+    pub fn info(self) -> &'static InstructionInfo {
+        &INSTRUCTIONS[self as usize]
+    }
+}
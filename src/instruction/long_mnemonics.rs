@@ -1,53 +1,62 @@
 pub type LongMnemonic = &'static str;
 
-pub const ALL_LONG_MNEMONICS: [&str; 46] = [
-    NOP,
-    RESET,
-    HALT,
-    IN,
-    OUT,
-    POP,
-    DUP,
-    PUSHPC,
-    POPPC,
-    POPSP,
-    SPTGT,
-    PUSHNZ,
-    SWAP,
-    PUSH0,
-    ADD,
-    SUB,
-    INC,
-    DEC,
-    MUL,
-    DIV,
-    XOR,
-    AND,
-    OR,
-    SHL,
-    SHR,
-    NOT,
-    BZ,
-    BNZ,
-    BEQ,
-    BGT,
-    BLT,
-    BGE,
-    LOOP,
-    ENDL,
-    BRAN,
-    BRAP,
-    TARGET,
-    SKIP1,
-    SKIP2,
-    SKIP3,
-    SKIP4,
-    SKIP5,
-    SKIP6,
-    SKIP7,
-    SKIP8,
-    SKIP9,
-];
+macro_rules! all_long_mnemonics {
+    ($($extra:ident),*) => { [
+        NOP,
+        RESET,
+        HALT,
+        IN,
+        OUT,
+        POP,
+        DUP,
+        PUSHPC,
+        POPPC,
+        POPSP,
+        SPTGT,
+        PUSHNZ,
+        SWAP,
+        PUSH0,
+        ADD,
+        SUB,
+        INC,
+        DEC,
+        MUL,
+        DIV,
+        XOR,
+        AND,
+        OR,
+        SHL,
+        SHR,
+        NOT,
+        BZ,
+        BNZ,
+        BEQ,
+        BGT,
+        BLT,
+        BGE,
+        LOOP,
+        ENDL,
+        BRAN,
+        BRAP,
+        TARGET,
+        SKIP1,
+        SKIP2,
+        SKIP3,
+        SKIP4,
+        SKIP5,
+        SKIP6,
+        SKIP7,
+        SKIP8,
+        SKIP9,
+        $($extra,)*
+    ] }
+}
+
+#[cfg(not(feature = "extended-isa"))]
+pub const ALL_LONG_MNEMONICS: [&str; 46] = all_long_mnemonics!();
+
+#[cfg(feature = "extended-isa")]
+pub const ALL_LONG_MNEMONICS: [&str; 53] = all_long_mnemonics!(BLE, BNE, ROT, OVER, MOD, BSLT, BSGT);
 
 pub const NOP: LongMnemonic    = "Nop";
 pub const RESET: LongMnemonic  = "Reset";
@@ -95,3 +104,18 @@ pub const SKIP6: LongMnemonic  = "Skip6";
 pub const SKIP7: LongMnemonic  = "Skip7";
 pub const SKIP8: LongMnemonic  = "Skip8";
 pub const SKIP9: LongMnemonic  = "Skip9";
+
+#[cfg(feature = "extended-isa")]
+pub const BLE: LongMnemonic    = "Ble";
+#[cfg(feature = "extended-isa")]
+pub const BNE: LongMnemonic    = "Bne";
+#[cfg(feature = "extended-isa")]
+pub const ROT: LongMnemonic    = "Rot";
+#[cfg(feature = "extended-isa")]
+pub const OVER: LongMnemonic   = "Over";
+#[cfg(feature = "extended-isa")]
+pub const MOD: LongMnemonic    = "Mod";
+#[cfg(feature = "extended-isa")]
+pub const BSLT: LongMnemonic   = "Bslt";
+#[cfg(feature = "extended-isa")]
+pub const BSGT: LongMnemonic   = "Bsgt";
@@ -1,53 +1,62 @@
 pub type Mnemonic = char;
 
-pub const ALL_MNEMONICS: [char; 46] = [
-    NOP,
-    RESET,
-    HALT,
-    IN,
-    OUT,
-    POP,
-    DUP,
-    PUSHPC,
-    POPPC,
-    POPSP,
-    SPTGT,
-    PUSHNZ,
-    SWAP,
-    PUSH0,
-    ADD,
-    SUB,
-    INC,
-    DEC,
-    MUL,
-    DIV,
-    XOR,
-    AND,
-    OR,
-    SHL,
-    SHR,
-    NOT,
-    BZ,
-    BNZ,
-    BEQ,
-    BGT,
-    BLT,
-    BGE,
-    LOOP,
-    ENDL,
-    BRAN,
-    BRAP,
-    TARGET,
-    SKIP1,
-    SKIP2,
-    SKIP3,
-    SKIP4,
-    SKIP5,
-    SKIP6,
-    SKIP7,
-    SKIP8,
-    SKIP9,
-];
+macro_rules! all_mnemonics {
+    ($($extra:ident),*) => { [
+        NOP,
+        RESET,
+        HALT,
+        IN,
+        OUT,
+        POP,
+        DUP,
+        PUSHPC,
+        POPPC,
+        POPSP,
+        SPTGT,
+        PUSHNZ,
+        SWAP,
+        PUSH0,
+        ADD,
+        SUB,
+        INC,
+        DEC,
+        MUL,
+        DIV,
+        XOR,
+        AND,
+        OR,
+        SHL,
+        SHR,
+        NOT,
+        BZ,
+        BNZ,
+        BEQ,
+        BGT,
+        BLT,
+        BGE,
+        LOOP,
+        ENDL,
+        BRAN,
+        BRAP,
+        TARGET,
+        SKIP1,
+        SKIP2,
+        SKIP3,
+        SKIP4,
+        SKIP5,
+        SKIP6,
+        SKIP7,
+        SKIP8,
+        SKIP9,
+        $($extra,)*
+    ] }
+}
+
+#[cfg(not(feature = "extended-isa"))]
+pub const ALL_MNEMONICS: [char; 46] = all_mnemonics!();
+
+#[cfg(feature = "extended-isa")]
+pub const ALL_MNEMONICS: [char; 53] = all_mnemonics!(BLE, BNE, ROT, OVER, MOD, BSLT, BSGT);
 
 pub const NOP: Mnemonic    = ';';
 pub const RESET: Mnemonic  = 'R';
@@ -95,3 +104,18 @@ pub const SKIP6: Mnemonic  = '6';
 pub const SKIP7: Mnemonic  = '7';
 pub const SKIP8: Mnemonic  = '8';
 pub const SKIP9: Mnemonic  = '9';
+
+#[cfg(feature = "extended-isa")]
+pub const BLE: Mnemonic    = '<';
+#[cfg(feature = "extended-isa")]
+pub const BNE: Mnemonic    = '#';
+#[cfg(feature = "extended-isa")]
+pub const ROT: Mnemonic    = '@';
+#[cfg(feature = "extended-isa")]
+pub const OVER: Mnemonic   = '$';
+#[cfg(feature = "extended-isa")]
+pub const MOD: Mnemonic    = '%';
+#[cfg(feature = "extended-isa")]
+pub const BSLT: Mnemonic   = '?';
+#[cfg(feature = "extended-isa")]
+pub const BSGT: Mnemonic   = '`';
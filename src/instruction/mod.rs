@@ -67,9 +67,26 @@
 //! 8       SKIP8   Skip over the next eight instructions
 //! 9       SKIP9   Skip over the next nine instructions
 //! ```
+//!
+//! ### Extended
+//!
+//! These opcodes are only assigned when the `extended-isa` feature is enabled,
+//! they are executed as NOP opcodes otherwise. Their mnemonics are symbols rather
+//! than letters, so that most text stored in a program loads as it does without the feature.
+//!
+//! ```text
+//! <       BLE     Branch on less than or equal
+//! #       BNE     Branch on not equal
+//! @       ROT     Rotate the top three stacked words
+//! $       OVER    Push the second stacked word
+//! %       MOD     Modulo of the top two stacked words
+//! ?       BSLT    Branch on signed less than
+//! `       BSGT    Branch on signed greater than
+//! ```
 
 use std::convert::From;
 
+pub mod info;
 pub mod mnemonics;
 pub mod long_mnemonics;
 pub mod op_codes;
@@ -77,6 +94,7 @@ pub mod op_codes;
 pub use self::op_codes::OpCode;
 pub use self::mnemonics::Mnemonic;
 pub use self::long_mnemonics::LongMnemonic;
+pub use self::info::{ControlFlow, Effects, InstructionInfo, INSTRUCTIONS, op_code_info, mnemonic_info};
 
 /// These are the opcodes of the Reustmann instruction set,
 /// shown with their single-character mnemonics and long mnemonics.
@@ -660,6 +678,108 @@ pub enum Instruction {
     /// NZ = no change
     /// ```
     Skip9 = op_codes::SKIP9 as isize,
+
+    /// Compare top two stacked words, branch if less than or equal
+    ///
+    /// mnemonic: `<`
+    ///
+    /// ```text
+    /// if *(SP + 1 mod L) <= *SP
+    ///     PC = PC + 2 mod L
+    /// else
+    ///     PC = PC + 1 mod L
+    /// SP = no change NZ = no change
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Ble = op_codes::BLE as isize,
+
+    /// Compare top two stacked words, branch if not equal
+    ///
+    /// mnemonic: `#`
+    ///
+    /// ```text
+    /// if *(SP + 1 mod L) .ne. *SP
+    ///     PC = PC + 2 mod L
+    /// else
+    ///     PC = PC + 1 mod L
+    /// SP = no change NZ = no change
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Bne = op_codes::BNE as isize,
+
+    /// Rotate the top three stacked words, the third one goes on top
+    ///
+    /// mnemonic: `@`
+    ///
+    /// ```text
+    /// Temp = *(SP + 2 mod L)
+    /// *(SP + 2 mod L) = *(SP + 1 mod L)
+    /// *(SP + 1 mod L) = *SP
+    /// *SP = Temp
+    /// PC = PC + 1 mod L
+    /// NZ = no change
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Rot = op_codes::ROT as isize,
+
+    /// Push a copy of the second stacked word
+    ///
+    /// mnemonic: `$`
+    ///
+    /// ```text
+    /// Temp = *(SP + 1 mod L)
+    /// SP = SP - 1 mod L
+    /// *SP = Temp
+    /// PC = PC + 1 mod L
+    /// NZ = true if the value pushed is nonzero, else false
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Over = op_codes::OVER as isize,
+
+    /// Push the remainder of the division of the top two stacked words
+    ///
+    /// mnemonic: `%`
+    ///
+    /// If the divisor is zero, the remainder is zero.
+    ///
+    /// ```text
+    /// SP = SP - 1 mod L
+    /// *SP = *(SP + 2 mod L) mod *(SP + 1 mod L)
+    /// PC = PC + 1 mod L
+    /// NZ = true if the result is nonzero, else false
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Mod = op_codes::MOD as isize,
+
+    /// Compare top two stacked words as W bits two's complement numbers,
+    /// branch if less than
+    ///
+    /// mnemonic: `?`
+    ///
+    /// ```text
+    /// if signed *(SP + 1 mod L) < signed *SP
+    ///     PC = PC + 2 mod L
+    /// else
+    ///     PC = PC + 1 mod L
+    /// SP = no change NZ = no change
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Bslt = op_codes::BSLT as isize,
+
+    /// Compare top two stacked words as W bits two's complement numbers,
+    /// branch if greater than
+    ///
+    /// mnemonic: `` ` ``
+    ///
+    /// ```text
+    /// if signed *(SP + 1 mod L) > signed *SP
+    ///     PC = PC + 2 mod L
+    /// else
+    ///     PC = PC + 1 mod L
+    /// SP = no change NZ = no change
+    /// ```
+    #[cfg(feature = "extended-isa")]
+    Bsgt = op_codes::BSGT as isize,
 }
 
 use self::Instruction::*;
@@ -667,109 +787,13 @@ use self::Instruction::*;
 /// Check if a op_code is a direct command
 /// or will be interpreted as NOP
 pub fn is_valid_op_code(op_code: OpCode) -> bool {
-    match op_code {
-              op_codes::NOP
-            | op_codes::RESET
-            | op_codes::HALT
-            | op_codes::IN
-            | op_codes::OUT
-            | op_codes::POP
-            | op_codes::DUP
-            | op_codes::PUSHPC
-            | op_codes::POPPC
-            | op_codes::POPSP
-            | op_codes::SPTGT
-            | op_codes::PUSHNZ
-            | op_codes::SWAP
-            | op_codes::PUSH0
-            | op_codes::ADD
-            | op_codes::SUB
-            | op_codes::INC
-            | op_codes::DEC
-            | op_codes::MUL
-            | op_codes::DIV
-            | op_codes::XOR
-            | op_codes::AND
-            | op_codes::OR
-            | op_codes::SHL
-            | op_codes::SHR
-            | op_codes::NOT
-            | op_codes::BZ
-            | op_codes::BNZ
-            | op_codes::BEQ
-            | op_codes::BGT
-            | op_codes::BLT
-            | op_codes::BGE
-            | op_codes::LOOP
-            | op_codes::ENDL
-            | op_codes::BRAN
-            | op_codes::BRAP
-            | op_codes::TARGET
-            | op_codes::SKIP1
-            | op_codes::SKIP2
-            | op_codes::SKIP3
-            | op_codes::SKIP4
-            | op_codes::SKIP5
-            | op_codes::SKIP6
-            | op_codes::SKIP7
-            | op_codes::SKIP8
-            | op_codes::SKIP9 => true,
-            _ => false
-        }
+    op_code_info(op_code).is_some()
 }
 
 /// Check if a op_code is a direct command
 /// or will be interpreted as NOP
 pub fn is_valid_mnemonic(mnemo: Mnemonic) -> bool {
-    match mnemo {
-              mnemonics::NOP
-            | mnemonics::RESET
-            | mnemonics::HALT
-            | mnemonics::IN
-            | mnemonics::OUT
-            | mnemonics::POP
-            | mnemonics::DUP
-            | mnemonics::PUSHPC
-            | mnemonics::POPPC
-            | mnemonics::POPSP
-            | mnemonics::SPTGT
-            | mnemonics::PUSHNZ
-            | mnemonics::SWAP
-            | mnemonics::PUSH0
-            | mnemonics::ADD
-            | mnemonics::SUB
-            | mnemonics::INC
-            | mnemonics::DEC
-            | mnemonics::MUL
-            | mnemonics::DIV
-            | mnemonics::XOR
-            | mnemonics::AND
-            | mnemonics::OR
-            | mnemonics::SHL
-            | mnemonics::SHR
-            | mnemonics::NOT
-            | mnemonics::BZ
-            | mnemonics::BNZ
-            | mnemonics::BEQ
-            | mnemonics::BGT
-            | mnemonics::BLT
-            | mnemonics::BGE
-            | mnemonics::LOOP
-            | mnemonics::ENDL
-            | mnemonics::BRAN
-            | mnemonics::BRAP
-            | mnemonics::TARGET
-            | mnemonics::SKIP1
-            | mnemonics::SKIP2
-            | mnemonics::SKIP3
-            | mnemonics::SKIP4
-            | mnemonics::SKIP5
-            | mnemonics::SKIP6
-            | mnemonics::SKIP7
-            | mnemonics::SKIP8
-            | mnemonics::SKIP9 => true,
-            _ => false
-        }
+    mnemonic_info(mnemo).is_some()
 }
 
 impl From<Mnemonic> for Instruction {
@@ -820,7 +844,21 @@ impl From<Mnemonic> for Instruction {
            mnemonics::SKIP7  => Skip7,
            mnemonics::SKIP8  => Skip8,
            mnemonics::SKIP9  => Skip9,
-           mnemonics::NOP | _ => Nop,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::BLE    => Ble,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::BNE    => Bne,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::ROT    => Rot,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::OVER   => Over,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::MOD    => Mod,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::BSLT   => Bslt,
+           #[cfg(feature = "extended-isa")]
+           mnemonics::BSGT   => Bsgt,
+           _ => Nop,
         }
     }
 }
@@ -874,6 +912,20 @@ impl From<Instruction> for Mnemonic {
             Skip7  => mnemonics::SKIP7,
             Skip8  => mnemonics::SKIP8,
             Skip9  => mnemonics::SKIP9,
+            #[cfg(feature = "extended-isa")]
+            Ble    => mnemonics::BLE,
+            #[cfg(feature = "extended-isa")]
+            Bne    => mnemonics::BNE,
+            #[cfg(feature = "extended-isa")]
+            Rot    => mnemonics::ROT,
+            #[cfg(feature = "extended-isa")]
+            Over   => mnemonics::OVER,
+            #[cfg(feature = "extended-isa")]
+            Mod    => mnemonics::MOD,
+            #[cfg(feature = "extended-isa")]
+            Bslt   => mnemonics::BSLT,
+            #[cfg(feature = "extended-isa")]
+            Bsgt   => mnemonics::BSGT,
         }
     }
 }
@@ -926,7 +978,21 @@ impl From<OpCode> for Instruction {
             op_codes::SKIP7  => Skip7,
             op_codes::SKIP8  => Skip8,
             op_codes::SKIP9  => Skip9,
-            op_codes::NOP | _ => Nop,
+            #[cfg(feature = "extended-isa")]
+            op_codes::BLE    => Ble,
+            #[cfg(feature = "extended-isa")]
+            op_codes::BNE    => Bne,
+            #[cfg(feature = "extended-isa")]
+            op_codes::ROT    => Rot,
+            #[cfg(feature = "extended-isa")]
+            op_codes::OVER   => Over,
+            #[cfg(feature = "extended-isa")]
+            op_codes::MOD    => Mod,
+            #[cfg(feature = "extended-isa")]
+            op_codes::BSLT   => Bslt,
+            #[cfg(feature = "extended-isa")]
+            op_codes::BSGT   => Bsgt,
+            _ => Nop,
         }
     }
 }
@@ -986,6 +1052,20 @@ impl From<Instruction> for &'static str {
             Skip7   => long_mnemonics::SKIP7,
             Skip8   => long_mnemonics::SKIP8,
             Skip9   => long_mnemonics::SKIP9,
+            #[cfg(feature = "extended-isa")]
+            Ble     => long_mnemonics::BLE,
+            #[cfg(feature = "extended-isa")]
+            Bne     => long_mnemonics::BNE,
+            #[cfg(feature = "extended-isa")]
+            Rot     => long_mnemonics::ROT,
+            #[cfg(feature = "extended-isa")]
+            Over    => long_mnemonics::OVER,
+            #[cfg(feature = "extended-isa")]
+            Mod     => long_mnemonics::MOD,
+            #[cfg(feature = "extended-isa")]
+            Bslt    => long_mnemonics::BSLT,
+            #[cfg(feature = "extended-isa")]
+            Bsgt    => long_mnemonics::BSGT,
         }
     }
 }
@@ -46,3 +46,18 @@ pub const SKIP6: OpCode  = 42;
 pub const SKIP7: OpCode  = 43;
 pub const SKIP8: OpCode  = 44;
 pub const SKIP9: OpCode  = 45;
+
+#[cfg(feature = "extended-isa")]
+pub const BLE: OpCode    = 46;
+#[cfg(feature = "extended-isa")]
+pub const BNE: OpCode    = 47;
+#[cfg(feature = "extended-isa")]
+pub const ROT: OpCode    = 48;
+#[cfg(feature = "extended-isa")]
+pub const OVER: OpCode   = 49;
+#[cfg(feature = "extended-isa")]
+pub const MOD: OpCode    = 50;
+#[cfg(feature = "extended-isa")]
+pub const BSLT: OpCode   = 51;
+#[cfg(feature = "extended-isa")]
+pub const BSGT: OpCode   = 52;
@@ -1,12 +1,15 @@
-use std::cmp::min;
 use std::io::{Read, Write};
-use std::u32;
 
-use crate::instruction::Instruction;
-use crate::instruction::is_valid_mnemonic;
+use crate::device::Device;
+use crate::dialect::{Dialect, DivByZero, DivResult};
+use crate::host_call::{HostCall, HostContext};
+use crate::instruction::is_valid_op_code;
 use crate::instruction::op_codes::*;
-use crate::memory::OpCodes;
+use crate::machine_io::{ByteStream, Encoding, MachineIo};
+use crate::memory::{Word, decode};
+use crate::observer::Observer;
 use crate::program::Program;
+use crate::watcher::{MemoryEvent, MemoryWatcher};
 
 /// Type used to return the execution status of a command
 pub type ExecutionSucceeded = bool;
@@ -15,23 +18,109 @@ pub type ExecutionSucceeded = bool;
 #[derive(Debug, Copy, Clone)]
 pub struct Statement(pub OpCode, pub ExecutionSucceeded);
 
+/// Everything an instruction did during one cycle, returned by
+/// [`Interpreter::step_detailed`](struct.Interpreter.html#method.step_detailed).
+#[derive(Debug, Copy, Clone)]
+pub struct StepDetails {
+    /// The opcode executed with its execution status.
+    pub statement: Statement,
+    /// The PC the instruction was executed at.
+    pub pc: usize,
+    /// The PC of the next instruction to execute.
+    pub next_pc: usize,
+    /// The SP before the instruction.
+    pub sp: usize,
+    /// The SP after the instruction.
+    pub next_sp: usize,
+    /// A branch, a skip, a loop or a jump moved the PC elsewhere than the next location.
+    pub branch_taken: bool,
+    /// The word pushed onto the stack, if any.
+    pub pushed: Option<Word>,
+    /// The word popped from the stack, if any.
+    pub popped: Option<Word>,
+    /// The word read by an `IN`.
+    pub input: Option<Word>,
+    /// The word written by an `OUT`.
+    pub output: Option<Word>,
+}
+
+// records what an instruction did, forwarding the callbacks to another observer
+struct StepRecorder<'a, O: ?Sized + 'a> {
+    observer: &'a mut O,
+    sp: usize,
+    read_top: Option<Word>,
+    written: Option<(usize, Word)>,
+    input: Option<Word>,
+    output: Option<Word>,
+    branch_taken: bool,
+}
+
+impl<'a, O: ?Sized + Observer> Observer for StepRecorder<'a, O> {
+    fn on_step(&mut self, pc: usize, op_code: OpCode) {
+        self.observer.on_step(pc, op_code)
+    }
+
+    fn on_memory_read(&mut self, addr: usize, value: Word) {
+        if addr == self.sp && self.read_top.is_none() {
+            self.read_top = Some(value);
+        }
+        self.observer.on_memory_read(addr, value)
+    }
+
+    fn on_memory_write(&mut self, addr: usize, old: Word, new: Word) {
+        self.written = Some((addr, new));
+        self.observer.on_memory_write(addr, old, new)
+    }
+
+    fn on_input(&mut self, value: Word, succeeded: bool) {
+        self.input = Some(value);
+        self.observer.on_input(value, succeeded)
+    }
+
+    fn on_output(&mut self, value: Word, succeeded: bool) {
+        self.output = Some(value);
+        self.observer.on_output(value, succeeded)
+    }
+
+    fn on_branch(&mut self, from: usize, to: usize) {
+        self.branch_taken = true;
+        self.observer.on_branch(from, to)
+    }
+
+    fn on_halt(&mut self, pc: usize) {
+        self.observer.on_halt(pc)
+    }
+}
+
 /// A Debug structure to help debugging :)
 #[derive(Debug)]
 pub struct DebugInfos {
-    pub memory: OpCodes,
+    pub memory: Vec<Word>,
     pub pc: usize,
     pub sp: usize,
     pub nz: bool
 }
 
+/// A device mapped onto the locations `start..start + len`.
+struct MappedDevice {
+    start: usize,
+    len: usize,
+    device: Box<dyn Device>,
+}
+
 /// The main interpreter, execute instructions, read from input,
 /// write to output
 pub struct Interpreter {
     arch_width: u8,      // [6..32)
-    memory: Vec<OpCode>, // [1..2^32)
-    pc: usize,
-    sp: usize,
-    nz: bool
+    pub(crate) memory: Vec<Word>,   // [1..2^32)
+    pub(crate) pc: usize,
+    pub(crate) sp: usize,
+    pub(crate) nz: bool,
+    encoding: Encoding,
+    dialect: Dialect,
+    watcher: Option<MemoryWatcher>,
+    devices: Vec<MappedDevice>,
+    host_calls: Vec<(OpCode, Box<dyn HostCall>)>,
 }
 
 impl Interpreter {
@@ -43,31 +132,32 @@ impl Interpreter {
         if arch_length == 0 || arch_length > u32::MAX as usize {
             return Err("Arch length need to be in the range [1..2^32)");
         }
-        if arch_width < 6 || arch_width > 32 {
+        if !(6..=32).contains(&arch_width) {
             return Err("Arch width need to be in the range [6..32)");
         }
-        let mut memory = Vec::with_capacity(arch_length);
-        for _ in 0..arch_length {
-            memory.push(NOP);
-        }
         Ok(Interpreter {
             arch_width: arch_width as u8,
-            memory,
+            memory: vec![NOP as Word; arch_length],
             pc: 0,
             sp: 0,
-            nz: false
+            nz: false,
+            encoding: Encoding::Byte,
+            dialect: Dialect::default(),
+            watcher: None,
+            devices: Vec::new(),
+            host_calls: Vec::new(),
         })
     }
 
     /// Copy your program in the memory of the machine, a reset is done after
     /// program was loaded.
     pub fn copy_program(&mut self, program: &Program) {
-        let mnemos = program.memory();
-        for i in 0..min(mnemos.len(), self.memory.len()) {
-            let mnemo = mnemos[i] as char;
-            self.memory[i] = if is_valid_mnemonic(mnemo) {
-                Into::<Instruction>::into(mnemo).into()
-            } else { mnemo as u8 };
+        let max_word = self.max_word();
+        for (cell, op_code) in self.memory.iter_mut().zip(program.op_codes().iter()) {
+            *cell = *op_code as Word & max_word;
+        }
+        if let Some(ref mut watcher) = self.watcher {
+            watcher.clear();
         }
         self.reset();
     }
@@ -82,6 +172,145 @@ impl Interpreter {
         self.arch_width as usize
     }
 
+    /// return the words stored in the interpreter memory
+    pub fn memory(&self) -> &[Word] {
+        &self.memory
+    }
+
+    /// return the program counter
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// return the stack pointer
+    pub fn sp(&self) -> usize {
+        self.sp
+    }
+
+    /// return the non-zero flag
+    pub fn nz(&self) -> bool {
+        self.nz
+    }
+
+    /// Move the program counter, modulo the arch length.
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc % self.memory.len();
+    }
+
+    /// Move the stack pointer, modulo the arch length.
+    pub fn set_sp(&mut self, sp: usize) {
+        self.sp = sp % self.memory.len();
+    }
+
+    /// Change the non-zero flag.
+    pub fn set_nz(&mut self, nz: bool) {
+        self.nz = nz;
+    }
+
+    /// Write a word in memory without executing anything,
+    /// `addr` is taken modulo the arch length and `word` is truncated
+    /// to the arch width. Mapped devices are not written.
+    pub fn poke(&mut self, addr: usize, word: Word) {
+        let addr = addr % self.memory.len();
+        self.memory[addr] = self.trunc(word);
+    }
+
+    /// return the encoding used by `step` to exchange words with byte streams
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Set the encoding used by `step` to exchange words with byte streams,
+    /// `Encoding::Byte` by default.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
+    /// return the semantics used for the behaviors the spec leaves open
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Set the semantics used for the behaviors the spec leaves open,
+    /// `Dialect::reustmann()` by default.
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    /// Start classifying memory locations as code or data,
+    /// the memory watcher is disabled by default.
+    pub fn enable_memory_watcher(&mut self) {
+        if self.watcher.is_none() {
+            self.watcher = Some(MemoryWatcher::new(self.memory.len()));
+        }
+    }
+
+    /// Stop watching memory accesses and forget the collected ones.
+    pub fn disable_memory_watcher(&mut self) {
+        self.watcher = None;
+    }
+
+    /// return the memory watcher, if enabled
+    pub fn memory_watcher(&self) -> Option<&MemoryWatcher> {
+        self.watcher.as_ref()
+    }
+
+    /// Take the self-modification and stack/code collision events
+    /// collected since the last call, empty if the memory watcher is disabled.
+    pub fn drain_memory_events(&mut self) -> Vec<MemoryEvent> {
+        match self.watcher {
+            Some(ref mut watcher) => watcher.drain_events(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Map a device onto the `len` locations starting at `start`,
+    /// reads and writes through the SP or the PC in this range are forwarded to the device.
+    ///
+    /// The memory keeps the last word written to each location of the range,
+    /// the instructions searching for a `LOOP` or a `TARGET` still look at it.
+    pub fn map_device<D: Device + 'static>(&mut self, start: usize, len: usize, device: D) -> Result<(), &'static str> {
+        if len == 0 || start >= self.memory.len() || len > self.memory.len() - start {
+            return Err("Device range need to be a non-empty range of the memory");
+        }
+        if self.devices.iter().any(|d| start < d.start + d.len && d.start < start + len) {
+            return Err("Device range overlaps an already mapped device");
+        }
+        self.devices.push(MappedDevice { start, len, device: Box::new(device) });
+        Ok(())
+    }
+
+    /// Unmap the device mapped at `start` and return it.
+    pub fn unmap_device(&mut self, start: usize) -> Option<Box<dyn Device>> {
+        let index = self.devices.iter().position(|d| d.start == start)?;
+        Some(self.devices.remove(index).device)
+    }
+
+    /// Return the ranges of the mapped devices, in mapping order.
+    pub fn device_ranges(&self) -> impl Iterator<Item=(usize, usize)> + '_ {
+        self.devices.iter().map(|d| (d.start, d.len))
+    }
+
+    /// Execute `handler` for the unassigned op code `op_code`,
+    /// replacing the handler already registered for it.
+    ///
+    /// The op codes without handler keep behaving like `NOP`.
+    pub fn register_host_call<H: HostCall + 'static>(&mut self, op_code: OpCode, handler: H) -> Result<(), &'static str> {
+        if is_valid_op_code(op_code) {
+            return Err("Host calls can only be registered for unassigned op codes");
+        }
+        self.unregister_host_call(op_code);
+        self.host_calls.push((op_code, Box::new(handler)));
+        Ok(())
+    }
+
+    /// Remove the handler of `op_code` and return it,
+    /// the op code behaves like `NOP` again.
+    pub fn unregister_host_call(&mut self, op_code: OpCode) -> Option<Box<dyn HostCall>> {
+        let index = self.host_calls.iter().position(|&(code, _)| code == op_code)?;
+        Some(self.host_calls.swap_remove(index).1)
+    }
+
     /// Reset `pc`, `sp` and `nz` to `0`, `0` and `false` respectively.
     #[inline]
     pub fn reset(&mut self) -> Statement {
@@ -102,248 +331,332 @@ impl Interpreter {
     }
 
     #[inline]
-    fn set_nz(&mut self, val: u8) {
+    fn update_nz(&mut self, val: Word) {
         self.nz = val != 0;
     }
 
     #[inline]
-    fn decrement_sp(&mut self) {
+    pub(crate) fn decrement_sp(&mut self) {
         self.sp = if self.sp == 0 { self.memory.len() - 1 } else { self.sp - 1 };
     }
 
     #[inline]
-    fn increment_sp(&mut self) {
+    pub(crate) fn increment_sp(&mut self) {
         self.sp = self.sp.wrapping_add(1) % self.memory.len();
     }
 
+    #[inline]
+    pub(crate) fn read<O: ?Sized + Observer>(&mut self, addr: usize, observer: &mut O) -> Word {
+        if let Some(ref mut watcher) = self.watcher {
+            watcher.on_read(addr);
+        }
+        let val = self.load(addr);
+        observer.on_memory_read(addr, val);
+        val
+    }
+
+    #[inline]
+    pub(crate) fn write<O: ?Sized + Observer>(&mut self, addr: usize, val: Word, observer: &mut O) {
+        if let Some(ref mut watcher) = self.watcher {
+            watcher.on_write(self.pc, addr, self.memory[addr], val);
+        }
+        observer.on_memory_write(addr, self.memory[addr], val);
+        self.memory[addr] = val;
+        if let Some(mapped) = self.devices.iter_mut().find(|d| addr >= d.start && addr < d.start + d.len) {
+            mapped.device.write(addr - mapped.start, val);
+        }
+    }
+
+    /// Read a location from its device, if mapped, or from the memory.
+    #[inline]
+    fn load(&mut self, addr: usize) -> Word {
+        if self.devices.is_empty() {
+            return self.memory[addr]
+        }
+        match self.devices.iter_mut().find(|d| addr >= d.start && addr < d.start + d.len) {
+            Some(mapped) => {
+                let val = mapped.device.read(addr - mapped.start);
+                self.trunc(val)
+            },
+            None => self.memory[addr],
+        }
+    }
+
     #[inline]
     /// Truncate a number to the machine word width.
-    fn trunc(&self, val: u8) -> u8 {
-        val & ((1 << self.arch_width) - 1)
+    pub(crate) fn trunc(&self, val: Word) -> Word {
+        val & self.max_word()
     }
 
-    // FIXME use Bytes iterator ?
-    fn execute<R: ?Sized + Read, W: ?Sized + Write>(&mut self, op: OpCode, input: &mut R, output: &mut W) -> Statement {
+    #[cfg(feature = "extended-isa")]
+    #[inline]
+    /// Interpret a word as a W bits two's complement number.
+    fn signed(&self, val: Word) -> i32 {
+        let shift = 32 - self.arch_width as u32;
+        ((val << shift) as i32) >> shift
+    }
+
+    /// Find the first location after the PC holding `op_code`,
+    /// continuing from location 0 if `wraps`.
+    fn search_forward(&self, op_code: OpCode, wraps: bool) -> Option<usize> {
+        let matches = |&i: &usize| self.memory[i] == op_code as Word;
+        let found = (self.pc + 1..self.memory.len()).find(matches);
+        if found.is_none() && wraps { (0..self.pc).find(matches) } else { found }
+    }
+
+    /// Find the first location before the PC holding `op_code`,
+    /// continuing from location L - 1 if `wraps`.
+    fn search_backward(&self, op_code: OpCode, wraps: bool) -> Option<usize> {
+        let matches = |&i: &usize| self.memory[i] == op_code as Word;
+        let found = (0..self.pc).rev().find(matches);
+        if found.is_none() && wraps { (self.pc + 1..self.memory.len()).rev().find(matches) } else { found }
+    }
+
+    #[inline]
+    /// The maximum value a word can hold.
+    fn max_word(&self) -> Word {
+        Word::MAX >> (32 - self.arch_width)
+    }
+
+    fn execute<M, O>(&mut self, op: OpCode, io: &mut M, observer: &mut O) -> Statement
+        where M: ?Sized + MachineIo, O: ?Sized + Observer {
         match op {
             RESET => self.reset(),
             HALT => Statement(op, true),
             IN => {
-                let mut status = true;
                 self.decrement_sp();
-                let mut buffer = [0; 1];
-                if let Err(_) = input.read(&mut buffer) { // FIXME save/return error ???
-                    status = false;
-                }
-                self.memory[self.sp] = buffer[0];
-                self.set_nz(buffer[0]);
+                let (val, status) = match io.read_word() {
+                    Ok(word) => (self.trunc(word.unwrap_or(0)), true),
+                    Err(_) => (0, false), // FIXME save/return error ???
+                };
+                self.write(self.sp, val, observer);
+                observer.on_input(val, status);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, status)
             },
             OUT => {
-                let mut status = true;
-                let val = self.memory[self.sp];
-                if let Err(_) = output.write(&[val]) { // FIXME save/return error ???
-                    status = false;
-                }
-                self.set_nz(val);
+                let val = self.read(self.sp, observer);
+                let status = io.write_word(val).is_ok(); // FIXME save/return error ???
+                observer.on_output(val, status);
+                self.update_nz(val);
                 self.increment_sp();
                 self.increment_pc();
                 Statement(op, status)
             },
             POP => {
-                let val = self.memory[self.sp];
-                self.set_nz(val);
+                let val = self.read(self.sp, observer);
+                self.update_nz(val);
                 self.increment_sp();
                 self.increment_pc();
                 Statement(op, true)
             },
             DUP => {
-                let tmp = self.memory[self.sp];
+                let tmp = self.read(self.sp, observer);
                 self.decrement_sp();
-                self.memory[self.sp] = tmp;
-                self.set_nz(tmp);
+                self.write(self.sp, tmp, observer);
+                self.update_nz(tmp);
                 self.increment_pc();
                 Statement(op, true)
             },
             PUSHPC => {
-                // let val = self.trunc(self.pc);
-                let val = self.pc as u8; // FIXME use trunc
+                let val = self.trunc(self.pc as Word);
                 self.decrement_sp();
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                if self.dialect.push_sets_nz {
+                    self.update_nz(val);
+                }
                 self.increment_pc();
                 Statement(op, true)
             },
             POPPC => {
-                self.pc = (self.memory[self.sp] as usize) % self.memory.len();
+                self.pc = (self.read(self.sp, observer) as usize) % self.memory.len();
                 self.increment_sp();
                 Statement(op, true)
             },
             POPSP => {
-                self.sp = (self.memory[self.sp] as usize) % self.memory.len();
+                self.sp = (self.read(self.sp, observer) as usize) % self.memory.len();
                 self.increment_pc();
                 Statement(op, true)
             },
             SPTGT => {
-                // find the next TARGET
-                if self.pc < self.memory.len() - 1 {
-                    for i in self.pc + 1..self.memory.len() {
-                        if self.memory[i] == TARGET {
-                            self.sp = i;
-                            break;
-                        }
-                    }
+                if let Some(i) = self.search_forward(TARGET, self.dialect.target_search_wraps) {
+                    self.sp = i;
                 }
                 self.increment_pc();
                 Statement(op, true)
             },
             PUSHNZ => {
-                let val = self.nz as u8;
+                let val = self.nz as Word;
                 self.decrement_sp();
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                if self.dialect.push_sets_nz {
+                    self.update_nz(val);
+                }
                 self.increment_pc();
                 Statement(op, true)
             },
             SWAP => {
-                let tmp = self.memory[self.sp];
+                let tmp = self.read(self.sp, observer);
                 let arch_len = self.memory.len();
-                self.memory[self.sp] = self.memory[(self.sp + 1) % arch_len];
-                self.memory[(self.sp + 1) % arch_len] = tmp;
+                let next = self.read((self.sp + 1) % arch_len, observer);
+                self.write(self.sp, next, observer);
+                self.write((self.sp + 1) % arch_len, tmp, observer);
                 self.increment_pc();
                 Statement(op, true)
             },
             PUSH0 => {
                 self.decrement_sp();
                 let val = 0;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             ADD => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
-                let val = a.wrapping_add(b);
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
+                let val = self.trunc(a.wrapping_add(b));
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             SUB => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
-                let val = a.wrapping_sub(b);
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
+                let val = self.trunc(a.wrapping_sub(b));
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             INC => {
-                let val = self.memory[self.sp].wrapping_add(1);
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let val = self.read(self.sp, observer).wrapping_add(1);
+                let val = self.trunc(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             DEC => {
-                let val = self.memory[self.sp].wrapping_sub(1);
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let val = self.read(self.sp, observer).wrapping_sub(1);
+                let val = self.trunc(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             MUL => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
-                let val = a.wrapping_mul(b);
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
+                let val = self.trunc(a.wrapping_mul(b));
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             DIV => {
-                self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
-                let val = if b != 0 { a.wrapping_div(b) } else { u8::max_value() };
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let by_zero = match self.dialect.div_by_zero {
+                    DivByZero::MaxWord => self.max_word(),
+                    DivByZero::Zero => 0,
+                };
+                match self.dialect.div_result {
+                    DivResult::Quotient => {
+                        self.decrement_sp();
+                        let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                        let b = self.read((self.sp + 1) % self.memory.len(), observer);
+                        let val = a.checked_div(b).unwrap_or(by_zero);
+                        self.write(self.sp, val, observer);
+                        self.update_nz(val);
+                    },
+                    DivResult::QuotientRemainder => {
+                        let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                        let b = self.read(self.sp, observer);
+                        let quotient = a.checked_div(b).unwrap_or(by_zero);
+                        let remainder = a.checked_rem(b).unwrap_or(0);
+                        self.write((self.sp + 1) % self.memory.len(), quotient, observer);
+                        self.write(self.sp, remainder, observer);
+                        self.update_nz(quotient);
+                    },
+                }
                 self.increment_pc();
                 Statement(op, true)
             },
             XOR => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
                 let val = a ^ b;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             AND => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
                 let val = a & b;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             OR => {
                 self.decrement_sp();
-                let a = self.memory[(self.sp + 2) % self.memory.len()];
-                let b = self.memory[(self.sp + 1) % self.memory.len()];
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
                 let val = a | b;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             SHL => {
-                let val = self.memory[self.sp] << 1;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let val = self.read(self.sp, observer) << 1;
+                let val = self.trunc(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             SHR => {
-                let val = self.memory[self.sp] >> 1;
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let val = self.read(self.sp, observer) >> 1;
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             NOT => {
-                let val = !self.memory[self.sp];
-                self.memory[self.sp] = val;
-                self.set_nz(val);
+                let val = !self.read(self.sp, observer);
+                let val = self.trunc(val);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
                 self.increment_pc();
                 Statement(op, true)
             },
             BZ => {
                 self.increment_pc();
-                if self.nz == false {
+                if !self.nz {
                     self.increment_pc();
                 }
                 Statement(op, true)
             },
             BNZ => {
                 self.increment_pc();
-                if self.nz == true {
+                if self.nz {
                     self.increment_pc();
                 }
                 Statement(op, true)
             },
             BEQ => {
                 self.increment_pc();
-                let a = self.memory[(self.sp + 1) % self.memory.len()];
-                let b = self.memory[self.sp];
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
                 if a == b {
                     self.increment_pc();
                 }
@@ -351,8 +664,8 @@ impl Interpreter {
             },
             BGT => {
                 self.increment_pc();
-                let a = self.memory[(self.sp + 1) % self.memory.len()];
-                let b = self.memory[self.sp];
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
                 if a > b {
                     self.increment_pc();
                 }
@@ -360,17 +673,17 @@ impl Interpreter {
             },
             BLT => {
                 self.increment_pc();
-                let a = self.memory[(self.sp + 1) % self.memory.len()];
-                let b = self.memory[self.sp];
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
                 if a < b {
                     self.increment_pc();
                 }
                 Statement(op, true)
             },
-            BGE => { // FIXME add BLE
+            BGE => {
                 self.increment_pc();
-                let a = self.memory[(self.sp + 1) % self.memory.len()];
-                let b = self.memory[self.sp];
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
                 if a >= b {
                     self.increment_pc();
                 }
@@ -382,49 +695,24 @@ impl Interpreter {
                 Statement(op, true)
             },
             ENDL => {
-                // find the preceding LOOP
-                let mut found = false;
-                for i in (0..self.pc).rev() {
-                    if self.memory[i] == LOOP {
-                        self.pc = (i + 1) % self.memory.len();
-                        found = true;
-                        break;
-                    }
-                }
-                if found == false {
-                    self.increment_pc();
+                match self.search_backward(LOOP, self.dialect.loop_search_wraps) {
+                    Some(i) => self.pc = (i + 1) % self.memory.len(),
+                    None => self.increment_pc(),
                 }
                 Statement(op, true)
             },
             BRAN => {
-                // find the next TARGET
-                let mut found = false;
-                if self.pc < self.memory.len() - 1 {
-                    for i in self.pc + 1..self.memory.len() {
-                        if self.memory[i] == TARGET {
-                            self.sp = i;
-                            found = true;
-                            break;
-                        }
-                    }
-                }
-                if found == false {
-                    self.increment_pc();
+                match self.search_forward(TARGET, self.dialect.target_search_wraps) {
+                    Some(i) if self.dialect.bran_sets_sp => self.sp = i,
+                    Some(i) => self.pc = (i + 1) % self.memory.len(),
+                    None => self.increment_pc(),
                 }
                 Statement(op, true)
             },
             BRAP => {
-                // find the preceding TARGET
-                let mut found = false;
-                for i in (0..self.pc).rev() {
-                    if self.memory[i] == TARGET {
-                        self.pc = (i + 1) % self.memory.len();
-                        found = true;
-                        break;
-                    }
-                }
-                if found == false {
-                    self.increment_pc();
+                match self.search_backward(TARGET, self.dialect.target_search_wraps) {
+                    Some(i) => self.pc = (i + 1) % self.memory.len(),
+                    None => self.increment_pc(),
                 }
                 Statement(op, true)
             },
@@ -468,25 +756,197 @@ impl Interpreter {
                 self.increment_pc_n(10);
                 Statement(op, true)
             },
-            NOP | _ => {
+            #[cfg(feature = "extended-isa")]
+            BLE => {
                 self.increment_pc();
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
+                if a <= b {
+                    self.increment_pc();
+                }
+                Statement(op, true)
+            },
+            #[cfg(feature = "extended-isa")]
+            BNE => {
+                self.increment_pc();
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
+                if a != b {
+                    self.increment_pc();
+                }
                 Statement(op, true)
             },
+            #[cfg(feature = "extended-isa")]
+            ROT => {
+                let arch_len = self.memory.len();
+                let a = self.read((self.sp + 2) % arch_len, observer);
+                let b = self.read((self.sp + 1) % arch_len, observer);
+                let c = self.read(self.sp, observer);
+                self.write((self.sp + 2) % arch_len, b, observer);
+                self.write((self.sp + 1) % arch_len, c, observer);
+                self.write(self.sp, a, observer);
+                self.increment_pc();
+                Statement(op, true)
+            },
+            #[cfg(feature = "extended-isa")]
+            OVER => {
+                let tmp = self.read((self.sp + 1) % self.memory.len(), observer);
+                self.decrement_sp();
+                self.write(self.sp, tmp, observer);
+                self.update_nz(tmp);
+                self.increment_pc();
+                Statement(op, true)
+            },
+            #[cfg(feature = "extended-isa")]
+            MOD => {
+                self.decrement_sp();
+                let a = self.read((self.sp + 2) % self.memory.len(), observer);
+                let b = self.read((self.sp + 1) % self.memory.len(), observer);
+                let val = a.checked_rem(b).unwrap_or(0);
+                self.write(self.sp, val, observer);
+                self.update_nz(val);
+                self.increment_pc();
+                Statement(op, true)
+            },
+            #[cfg(feature = "extended-isa")]
+            BSLT => {
+                self.increment_pc();
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
+                if self.signed(a) < self.signed(b) {
+                    self.increment_pc();
+                }
+                Statement(op, true)
+            },
+            #[cfg(feature = "extended-isa")]
+            BSGT => {
+                self.increment_pc();
+                let a = self.read((self.sp + 1) % self.memory.len(), observer);
+                let b = self.read(self.sp, observer);
+                if self.signed(a) > self.signed(b) {
+                    self.increment_pc();
+                }
+                Statement(op, true)
+            },
+            _ => match self.host_calls.iter().position(|&(code, _)| code == op) {
+                Some(index) => self.host_call(index, op, io, observer),
+                None => {
+                    self.increment_pc();
+                    Statement(op, true)
+                },
+            },
         }
     }
 
+    fn host_call<M, O>(&mut self, index: usize, op: OpCode, mut io: &mut M, mut observer: &mut O) -> Statement
+        where M: ?Sized + MachineIo, O: ?Sized + Observer {
+        // the handler is taken out while it borrows the interpreter
+        let (code, mut handler) = self.host_calls.swap_remove(index);
+        let (status, resume_at) = {
+            let mut context = HostContext { interpreter: self, io: &mut io, observer: &mut observer, resume_at: None };
+            (handler.call(&mut context), context.resume_at)
+        };
+        self.host_calls.push((code, handler));
+        match resume_at {
+            Some(pc) => self.pc = pc,
+            None => self.increment_pc(),
+        }
+        Statement(op, status)
+    }
+
     /// Use [Empty](https://doc.rust-lang.org/std/io/struct.Empty.html) and/or
     /// [Sink](https://doc.rust-lang.org/std/io/struct.Sink.html)
     /// if you don't want to give input and/or output.
     pub fn step<R: ?Sized + Read, W: ?Sized + Write>(&mut self, input: &mut R, output: &mut W) -> Statement {
-        let instr = self.memory[self.pc];
-        self.execute(instr, input, output)
+        self.step_with(input, output, &mut ())
+    }
+
+    /// Execute one instruction like [`step`](#method.step),
+    /// calling the `observer` callbacks as it goes.
+    pub fn step_with<R, W, O>(&mut self, input: &mut R, output: &mut W, observer: &mut O) -> Statement
+        where R: ?Sized + Read, W: ?Sized + Write, O: ?Sized + Observer {
+        let (encoding, arch_width) = (self.encoding, self.arch_width());
+        self.step_io_with(&mut ByteStream::with_encoding(input, output, encoding, arch_width), observer)
+    }
+
+    /// Execute one instruction, `IN` and `OUT` exchange whole words with `io`.
+    pub fn step_io<M: ?Sized + MachineIo>(&mut self, io: &mut M) -> Statement {
+        self.step_io_with(io, &mut ())
+    }
+
+    /// Execute one instruction like [`step_io`](#method.step_io),
+    /// calling the `observer` callbacks as it goes.
+    pub fn step_io_with<M, O>(&mut self, io: &mut M, observer: &mut O) -> Statement
+        where M: ?Sized + MachineIo, O: ?Sized + Observer {
+
+        let pc = self.pc;
+        let word = self.load(pc);
+        let instr = decode(word);
+        if let Some(ref mut watcher) = self.watcher {
+            watcher.on_execute(pc, instr);
+        }
+        observer.on_step(pc, instr);
+        let statement = self.execute(instr, io, observer);
+        for mapped in &mut self.devices {
+            mapped.device.tick();
+        }
+        // a BRAN that sets the SP leaves the PC in place without branching
+        let stays = instr == BRAN && self.dialect.bran_sets_sp;
+        if instr == HALT {
+            observer.on_halt(pc);
+        } else if self.pc != (pc + 1) % self.memory.len() && !stays {
+            observer.on_branch(pc, self.pc);
+        }
+        statement
+    }
+
+    /// Execute one instruction like [`step`](#method.step)
+    /// and report everything it did.
+    pub fn step_detailed<R: ?Sized + Read, W: ?Sized + Write>(&mut self, input: &mut R, output: &mut W) -> StepDetails {
+        self.step_detailed_with(input, output, &mut ())
+    }
+
+    /// Execute one instruction like [`step_detailed`](#method.step_detailed),
+    /// calling the `observer` callbacks as it goes.
+    pub fn step_detailed_with<R, W, O>(&mut self, input: &mut R, output: &mut W, observer: &mut O) -> StepDetails
+        where R: ?Sized + Read, W: ?Sized + Write, O: ?Sized + Observer {
+        let (pc, sp) = (self.pc, self.sp);
+        let mut recorder = StepRecorder {
+            observer,
+            sp,
+            read_top: None,
+            written: None,
+            input: None,
+            output: None,
+            branch_taken: false,
+        };
+        let statement = self.step_with(input, output, &mut recorder);
+        let (next_pc, next_sp) = (self.pc, self.sp);
+
+        let pushed = match recorder.written {
+            Some((addr, val)) if addr == next_sp && next_sp != sp => Some(val),
+            _ => None,
+        };
+        let popped = if pushed.is_none() && next_sp != sp { recorder.read_top } else { None };
+
+        StepDetails {
+            statement,
+            pc,
+            next_pc,
+            sp,
+            next_sp,
+            branch_taken: recorder.branch_taken,
+            pushed,
+            popped,
+            input: recorder.input,
+            output: recorder.output,
+        }
     }
 
     /// Get a debug struct that can help for debugging programs
     pub fn debug_infos(&self) -> DebugInfos {
        DebugInfos {
-            memory: OpCodes(self.memory.clone()),
+            memory: self.memory.clone(),
             pc: self.pc,
             sp: self.sp,
             nz: self.nz
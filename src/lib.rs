@@ -69,6 +69,15 @@
 //! immediately readjusted to the address modulo **L**. This implies that it is
 //! impossible to address a non-existent memory location.
 //!
+//! The behaviors left open by this description, like the result of a division
+//! by zero or whether the searches for a `TARGET` wrap around, are chosen by the
+//! [`Dialect`](dialect/struct.Dialect.html) of the interpreter.
+//! The [`reference`](reference/index.html) module holds a literal, slow
+//! implementation of the `iota` dialect to check the interpreter against.
+//!
+//! The [`device`](device/index.html) module lets ranges of locations be
+//! mapped onto devices like a random number port or a framebuffer.
+//!
 //! Each memory location can store unsigned values in the range [0 .. 2 **W**).
 //! The memory locations are all writable. Instruction opcodes are stored in
 //! memory by their numeric values as defined below.
@@ -85,6 +94,10 @@
 //! is not a defined opcode number, it is executed as if it were a NOP opcode.
 //! This implies there are no instruction faults.
 //!
+//! A host can give meaning to some of these undefined opcode numbers with
+//! [`Interpreter::register_host_call`](struct.Interpreter.html#method.register_host_call),
+//! the other ones are still executed as NOP opcodes.
+//!
 //! ## Input and Output
 //! The Reustmann instruction set has one IN and one OUT instruction.
 //! These are character-oriented, regardless of the
@@ -94,6 +107,9 @@
 //! instruction pops the top of the Reustmann stack, casts the value to a (char)
 //! type, and sends it to the abstract output stream.
 //!
+//! The [`machine_io`](machine_io/index.html) module lets **IN** and **OUT**
+//! exchange whole **W** bits words with a device instead of chars.
+//!
 //! ## Source Code Representation
 //! For Reustmann machines of rank **W** ≤ 8, the preferred source code format
 //! is a string of characters of length ≤ L. When loaded into Reustmann memory,
@@ -165,13 +181,30 @@
 // but this already exist !!!
 mod program;
 mod interpreter;
+mod host_call;
+mod observer;
+mod watcher;
 
+pub mod analysis;
+pub mod conformance;
+pub mod corpus;
+pub mod device;
+pub mod dialect;
 pub mod instruction;
+pub mod machine_io;
 pub mod memory;
+pub mod reference;
 
 // /// All instructions used in the Reustmann architecture.
 // pub use instruction::op_codes::OpCode;
 // pub use instruction::{Mnemonic, LongMnemonic};
 
 pub use program::Program;
-pub use interpreter::{Interpreter, Statement, DebugInfos};
+pub use device::Device;
+pub use dialect::Dialect;
+pub use host_call::{HostCall, HostContext};
+pub use interpreter::{Interpreter, Statement, StepDetails, DebugInfos};
+pub use machine_io::{Encoding, MachineIo};
+pub use memory::Word;
+pub use observer::Observer;
+pub use watcher::{CellAccess, MemoryEvent, MemoryEventKind, MemoryWatcher};
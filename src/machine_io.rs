@@ -0,0 +1,249 @@
+//! Devices used by the `IN` and `OUT` instructions.
+//!
+//! The interpreter exchanges whole words with a [`MachineIo`](trait.MachineIo.html),
+//! the adapters of this module connect it to byte streams, queues or closures.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Write};
+use std::str::{self, FromStr};
+
+use crate::memory::Word;
+
+/// A device the interpreter reads words from and writes words to.
+///
+/// The interpreter truncates the words read to its arch width.
+pub trait MachineIo {
+    /// Read the next word, `None` if there is no more input.
+    fn read_word(&mut self) -> io::Result<Option<Word>>;
+
+    /// Write a word.
+    fn write_word(&mut self, word: Word) -> io::Result<()>;
+}
+
+impl<M: ?Sized + MachineIo> MachineIo for &mut M {
+    fn read_word(&mut self) -> io::Result<Option<Word>> {
+        (**self).read_word()
+    }
+
+    fn write_word(&mut self, word: Word) -> io::Result<()> {
+        (**self).write_word(word)
+    }
+}
+
+/// Fill `buf` from `input`, retrying interrupted and short reads.
+///
+/// Return the number of bytes read, less than the length of `buf` only at end of input.
+fn read_full<R: ?Sized + Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match input.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+/// How words are encoded in a byte stream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// One byte per word, the word is truncated to a char as it is output.
+    #[default]
+    Byte,
+    /// A word is a Unicode code point encoded in UTF-8,
+    /// code points above 2^W - 1 are truncated when input.
+    Utf8,
+    /// A word is a UTF-16 code unit, two bytes least significant first.
+    Utf16,
+    /// A word is stored in as many bytes as needed to hold W bits,
+    /// least significant byte first.
+    RawLittleEndian,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match *self {
+            Encoding::Byte => "byte",
+            Encoding::Utf8 => "utf8",
+            Encoding::Utf16 => "utf16",
+            Encoding::RawLittleEndian => "raw",
+        })
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "byte" => Ok(Encoding::Byte),
+            "utf8" | "utf-8" => Ok(Encoding::Utf8),
+            "utf16" | "utf-16" => Ok(Encoding::Utf16),
+            "raw" => Ok(Encoding::RawLittleEndian),
+            _ => Err(format!("invalid encoding {:?}, expected byte, utf8, utf16 or raw", s)),
+        }
+    }
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Exchange words with a byte stream using an [`Encoding`](enum.Encoding.html).
+///
+/// By default a word is one byte: a char is read by `IN`
+/// and the word is truncated to a char by `OUT`.
+#[derive(Debug)]
+pub struct ByteStream<R, W> {
+    input: R,
+    output: W,
+    encoding: Encoding,
+    arch_width: usize,
+}
+
+impl<R: Read, W: Write> ByteStream<R, W> {
+    /// Exchange one byte per word.
+    pub fn new(input: R, output: W) -> ByteStream<R, W> {
+        ByteStream { input, output, encoding: Encoding::Byte, arch_width: 8 }
+    }
+
+    /// Exchange words of `arch_width` bits using the given encoding.
+    pub fn with_encoding(input: R, output: W, encoding: Encoding, arch_width: usize) -> ByteStream<R, W> {
+        ByteStream { input, output, encoding, arch_width }
+    }
+
+    /// Return the input and the output streams.
+    pub fn into_inner(self) -> (R, W) {
+        (self.input, self.output)
+    }
+
+    /// Read exactly `buf.len()` bytes, `false` if the input was already at its end.
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        match read_full(&mut self.input, buf)? {
+            0 => Ok(false),
+            n if n < buf.len() => Err(io::Error::new(ErrorKind::UnexpectedEof, "partial word read")),
+            _ => Ok(true),
+        }
+    }
+
+    fn read_little_endian(&mut self, len: usize) -> io::Result<Option<Word>> {
+        let mut buf = [0; 4];
+        let buf = &mut buf[..len];
+        if !self.read_exact(buf)? {
+            return Ok(None)
+        }
+        Ok(Some(buf.iter().rev().fold(0, |word, &b| (word << 8) | b as Word)))
+    }
+
+    fn read_utf8(&mut self) -> io::Result<Option<Word>> {
+        let mut buf = [0; 4];
+        if !self.read_exact(&mut buf[..1])? {
+            return Ok(None)
+        }
+        let len = match buf[0] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(invalid_data("invalid UTF-8 leading byte")),
+        };
+        if len > 1 && !self.read_exact(&mut buf[1..len])? {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "partial word read"))
+        }
+        match str::from_utf8(&buf[..len]) {
+            Ok(s) => Ok(s.chars().next().map(|c| c as Word)),
+            Err(_) => Err(invalid_data("invalid UTF-8 sequence")),
+        }
+    }
+
+    fn write_little_endian(&mut self, word: Word, len: usize) -> io::Result<()> {
+        let bytes = [word as u8, (word >> 8) as u8, (word >> 16) as u8, (word >> 24) as u8];
+        self.output.write_all(&bytes[..len])
+    }
+}
+
+impl<R: Read, W: Write> MachineIo for ByteStream<R, W> {
+    fn read_word(&mut self) -> io::Result<Option<Word>> {
+        match self.encoding {
+            Encoding::Byte => self.read_little_endian(1),
+            Encoding::Utf8 => self.read_utf8(),
+            Encoding::Utf16 => self.read_little_endian(2),
+            Encoding::RawLittleEndian => {
+                let len = self.arch_width.div_ceil(8).clamp(1, 4);
+                self.read_little_endian(len)
+            },
+        }
+    }
+
+    fn write_word(&mut self, word: Word) -> io::Result<()> {
+        match self.encoding {
+            Encoding::Byte => self.write_little_endian(word, 1),
+            Encoding::Utf8 => {
+                let c = char::from_u32(word).ok_or_else(|| invalid_data("invalid Unicode code point"))?;
+                let mut buf = [0; 4];
+                self.output.write_all(c.encode_utf8(&mut buf).as_bytes())
+            },
+            Encoding::Utf16 => self.write_little_endian(word, 2),
+            Encoding::RawLittleEndian => {
+                let len = self.arch_width.div_ceil(8).clamp(1, 4);
+                self.write_little_endian(word, len)
+            },
+        }
+    }
+}
+
+/// Exchange words with in-memory queues, useful to feed test vectors.
+#[derive(Debug, Default, Clone)]
+pub struct QueueIo {
+    /// The words not yet read by the interpreter.
+    pub input: VecDeque<Word>,
+    /// The words written by the interpreter.
+    pub output: Vec<Word>,
+}
+
+impl QueueIo {
+    /// Create a queue that will give the `input` words in order.
+    pub fn new<I: IntoIterator<Item=Word>>(input: I) -> QueueIo {
+        QueueIo { input: input.into_iter().collect(), output: Vec::new() }
+    }
+}
+
+impl MachineIo for QueueIo {
+    fn read_word(&mut self) -> io::Result<Option<Word>> {
+        Ok(self.input.pop_front())
+    }
+
+    fn write_word(&mut self, word: Word) -> io::Result<()> {
+        self.output.push(word);
+        Ok(())
+    }
+}
+
+/// Exchange words with closures, created by [`fn_io`](fn.fn_io.html).
+#[derive(Debug)]
+pub struct FnIo<F, G> {
+    read: F,
+    write: G,
+}
+
+/// Use a closure to give the words to read and another to receive the words written.
+pub fn fn_io<F, G>(read: F, write: G) -> FnIo<F, G>
+    where F: FnMut() -> Option<Word>, G: FnMut(Word) {
+    FnIo { read, write }
+}
+
+impl<F, G> MachineIo for FnIo<F, G>
+    where F: FnMut() -> Option<Word>, G: FnMut(Word) {
+
+    fn read_word(&mut self) -> io::Result<Option<Word>> {
+        Ok((self.read)())
+    }
+
+    fn write_word(&mut self, word: Word) -> io::Result<()> {
+        (self.write)(word);
+        Ok(())
+    }
+}
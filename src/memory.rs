@@ -1,7 +1,16 @@
 use std::ops::Deref;
 
 use crate::instruction::{Instruction, Mnemonic, LongMnemonic};
-use crate::instruction::op_codes::OpCode;
+use crate::instruction::op_codes::{self, OpCode};
+
+/// A memory location, the machine truncates it to its arch width.
+pub type Word = u32;
+
+/// Return the op code executed when the PC points to a word,
+/// words too large to be an op code are executed as a NOP.
+pub fn decode(word: Word) -> OpCode {
+    if word > OpCode::MAX as Word { op_codes::NOP } else { word as OpCode }
+}
 
 /// A struct that get all instruction in bytes (used in the Interpreter).
 #[derive(Debug, Clone)]
@@ -47,9 +56,9 @@ impl From<Mnemonics> for OpCodes {
     }
 }
 
-impl<'a> From<&'a Mnemonics> for OpCodes {
+impl From<&Mnemonics> for OpCodes {
     fn from(mnemos: &Mnemonics) -> Self {
-        let &Mnemonics(ref mnemos) = mnemos;
+        let Mnemonics(mnemos) = mnemos;
         let mut op_codes = Vec::with_capacity(mnemos.len());
         for instr in mnemos.iter() {
             op_codes.push(Into::<Instruction>::into(*instr).into());
@@ -69,9 +78,9 @@ impl From<OpCodes> for Mnemonics {
     }
 }
 
-impl<'a> From<&'a OpCodes> for Mnemonics {
+impl From<&OpCodes> for Mnemonics {
     fn from(op_codes: &OpCodes) -> Self {
-        let &OpCodes(ref op_codes) = op_codes;
+        let OpCodes(op_codes) = op_codes;
         let mut mnemos = Vec::with_capacity(op_codes.len());
         for instr in op_codes {
             let instr: Instruction = (*instr).into();
@@ -92,9 +101,9 @@ impl From<OpCodes> for LongMnemonics {
     }
 }
 
-impl<'a> From<&'a OpCodes> for LongMnemonics {
+impl From<&OpCodes> for LongMnemonics {
     fn from(op_codes: &OpCodes) -> Self {
-        let &OpCodes(ref op_codes) = op_codes;
+        let OpCodes(op_codes) = op_codes;
         let mut lmnemos = Vec::with_capacity(op_codes.len());
         for instr in op_codes {
             lmnemos.push(Into::<Instruction>::into(*instr).into());
@@ -114,9 +123,9 @@ impl From<Mnemonics> for LongMnemonics {
     }
 }
 
-impl<'a> From<&'a Mnemonics> for LongMnemonics {
+impl From<&Mnemonics> for LongMnemonics {
     fn from(mnemos: &Mnemonics) -> Self {
-        let &Mnemonics(ref mnemos) = mnemos;
+        let Mnemonics(mnemos) = mnemos;
         let mut lmnemos = Vec::with_capacity(mnemos.len());
         for instr in mnemos {
             lmnemos.push(Into::<Instruction>::into(*instr).into());
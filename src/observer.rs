@@ -0,0 +1,69 @@
+use crate::instruction::op_codes::OpCode;
+use crate::memory::Word;
+
+/// Callbacks called by the interpreter while it executes instructions.
+///
+/// Every method does nothing by default, implement only the ones you need.
+/// The unit type `()` is the observer used by [`Interpreter::step`],
+/// its empty callbacks are optimized away.
+///
+/// [`Interpreter::step`]: struct.Interpreter.html#method.step
+#[allow(unused_variables)]
+pub trait Observer {
+    /// Called before the instruction at `pc` is executed.
+    fn on_step(&mut self, pc: usize, op_code: OpCode) {}
+
+    /// Called when a word is read through the SP.
+    fn on_memory_read(&mut self, addr: usize, value: Word) {}
+
+    /// Called when a word is written through the SP.
+    fn on_memory_write(&mut self, addr: usize, old: Word, new: Word) {}
+
+    /// Called when an `IN` pushed `value`, `succeeded` is `false` if the input failed.
+    fn on_input(&mut self, value: Word, succeeded: bool) {}
+
+    /// Called when an `OUT` popped `value`, `succeeded` is `false` if the output failed.
+    fn on_output(&mut self, value: Word, succeeded: bool) {}
+
+    /// Called when an instruction moved the PC elsewhere than the next location,
+    /// skips, loops, branches, `POPPC` and `RESET` included.
+    ///
+    /// A `BRAN` that sets the SP (`Dialect::bran_sets_sp`) leaves the PC
+    /// on itself and is not reported.
+    fn on_branch(&mut self, from: usize, to: usize) {}
+
+    /// Called when a `HALT` is executed at `pc`.
+    fn on_halt(&mut self, pc: usize) {}
+}
+
+impl Observer for () {}
+
+impl<O: ?Sized + Observer> Observer for &mut O {
+    fn on_step(&mut self, pc: usize, op_code: OpCode) {
+        (**self).on_step(pc, op_code)
+    }
+
+    fn on_memory_read(&mut self, addr: usize, value: Word) {
+        (**self).on_memory_read(addr, value)
+    }
+
+    fn on_memory_write(&mut self, addr: usize, old: Word, new: Word) {
+        (**self).on_memory_write(addr, old, new)
+    }
+
+    fn on_input(&mut self, value: Word, succeeded: bool) {
+        (**self).on_input(value, succeeded)
+    }
+
+    fn on_output(&mut self, value: Word, succeeded: bool) {
+        (**self).on_output(value, succeeded)
+    }
+
+    fn on_branch(&mut self, from: usize, to: usize) {
+        (**self).on_branch(from, to)
+    }
+
+    fn on_halt(&mut self, pc: usize) {
+        (**self).on_halt(pc)
+    }
+}
@@ -1,6 +1,9 @@
 use std::{fs, io};
 use std::path::Path;
 
+use crate::instruction::{Instruction, is_valid_mnemonic};
+use crate::memory::OpCodes;
+
 /// A set of instructions that can be given to an interpreter.
 pub struct Program(Vec<u8>);
 
@@ -21,5 +24,18 @@ impl Program {
     pub fn memory(&self) -> &[u8] {
         &self.0
     }
+
+    /// Get the op codes as they are copied in the interpreter memory.
+    ///
+    /// Each char that is a mnemonic is stored as its op code,
+    /// any other char is stored as is.
+    pub fn op_codes(&self) -> OpCodes {
+        OpCodes(self.0.iter().map(|&mnemo| {
+            let mnemo = mnemo as char;
+            if is_valid_mnemonic(mnemo) {
+                Into::<Instruction>::into(mnemo).into()
+            } else { mnemo as u8 }
+        }).collect())
+    }
 }
 
@@ -0,0 +1,428 @@
+//! A deliberately simple interpreter following the instruction docs literally,
+//! and a harness running it in lockstep with another engine.
+//!
+//! The [`Reference`](struct.Reference.html) favors being obviously right
+//! over being fast: every location is computed as written in the equations
+//! of the [`Instruction`](../instruction/enum.Instruction.html) docs,
+//! which are the `Dialect::iota()` semantics. Use it as an oracle:
+//!
+//! ```
+//! use reustmann::{Dialect, Interpreter};
+//! use reustmann::reference;
+//!
+//! let divergence = reference::fuzz(42, 100, 200, |arch_length, arch_width| {
+//!     let mut interpreter = Interpreter::new(arch_length, arch_width).unwrap();
+//!     interpreter.set_dialect(Dialect::iota());
+//!     interpreter
+//! });
+//! if let Some(divergence) = divergence {
+//!     panic!("{}", divergence);
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::conformance::{Engine, MachineState};
+use crate::device::{Device, RandomPort};
+use crate::instruction::INSTRUCTIONS;
+use crate::instruction::op_codes::*;
+use crate::interpreter::ExecutionSucceeded;
+use crate::machine_io::{MachineIo, QueueIo};
+use crate::memory::Word;
+
+/// The reference interpreter, see the [module documentation](index.html).
+#[derive(Debug, Clone)]
+pub struct Reference {
+    arch_width: usize,
+    memory: Vec<Word>,
+    pc: usize,
+    sp: usize,
+    nz: bool,
+}
+
+impl Reference {
+    /// Create a reference machine, with the same ranges as `Interpreter::new`.
+    pub fn new(arch_length: usize, arch_width: usize) -> Result<Reference, &'static str> {
+        if arch_length == 0 || arch_length > u32::MAX as usize {
+            return Err("Arch length need to be in the range [1..2^32)");
+        }
+        if !(6..=32).contains(&arch_width) {
+            return Err("Arch width need to be in the range [6..32)");
+        }
+        Ok(Reference { arch_width, memory: vec![NOP as Word; arch_length], pc: 0, sp: 0, nz: false })
+    }
+
+    fn len(&self) -> usize {
+        self.memory.len()
+    }
+
+    // X trunc W
+    fn trunc(&self, x: u64) -> Word {
+        (x & ((1u64 << self.arch_width) - 1)) as Word
+    }
+
+    // the word as a W bits two's complement number
+    #[cfg(feature = "extended-isa")]
+    fn signed(&self, x: Word) -> i64 {
+        let x = x as i64;
+        if x >> (self.arch_width - 1) & 1 == 1 { x - (1i64 << self.arch_width) } else { x }
+    }
+
+    // *(SP + n mod L)
+    fn get(&self, n: usize) -> u64 {
+        self.memory[(self.sp + n) % self.len()] as u64
+    }
+
+    // *(SP + n mod L) = x trunc W
+    fn set(&mut self, n: usize, x: u64) {
+        let addr = (self.sp + n) % self.len();
+        self.memory[addr] = self.trunc(x);
+    }
+
+    // SP = SP - 1 mod L
+    fn sp_minus_one(&mut self) {
+        self.sp = (self.sp + self.len() - 1) % self.len();
+    }
+
+    // SP = SP + 1 mod L
+    fn sp_plus_one(&mut self) {
+        self.sp = (self.sp + 1) % self.len();
+    }
+
+    // PC = PC + n mod L
+    fn pc_plus(&mut self, n: usize) {
+        self.pc = (self.pc + n) % self.len();
+    }
+
+    fn branch_if(&mut self, condition: bool) {
+        self.pc_plus(if condition { 2 } else { 1 });
+    }
+
+    /// Execute one instruction, return its execution status.
+    pub fn step(&mut self, io: &mut dyn MachineIo) -> ExecutionSucceeded {
+        let word = self.memory[self.pc];
+        let op_code = if word <= 255 { word as OpCode } else { NOP };
+        let mut status = true;
+
+        match op_code {
+            RESET => {
+                self.pc = 0;
+                self.sp = 0;
+                self.nz = false;
+            },
+            HALT => (),
+            IN => {
+                let x = match io.read_word() {
+                    Ok(Some(x)) => x as u64,
+                    Ok(None) => 0,
+                    Err(_) => { status = false; 0 },
+                };
+                self.sp_minus_one();
+                self.set(0, x);
+                self.nz = self.get(0) != 0;
+                self.pc_plus(1);
+            },
+            OUT => {
+                let x = self.get(0);
+                status = io.write_word(x as Word).is_ok();
+                self.sp_plus_one();
+                self.pc_plus(1);
+                self.nz = x != 0;
+            },
+            POP => {
+                self.nz = self.get(0) != 0;
+                self.sp_plus_one();
+                self.pc_plus(1);
+            },
+            DUP => {
+                let temp = self.get(0);
+                self.sp_minus_one();
+                self.set(0, temp);
+                self.pc_plus(1);
+                self.nz = temp != 0;
+            },
+            PUSHPC => {
+                let pc = self.pc as u64;
+                self.sp_minus_one();
+                self.set(0, pc);
+                self.pc_plus(1);
+            },
+            POPPC => {
+                self.pc = (self.get(0) % self.len() as u64) as usize;
+                self.sp_plus_one();
+            },
+            POPSP => {
+                self.sp = (self.get(0) % self.len() as u64) as usize;
+                self.pc_plus(1);
+            },
+            SPTGT => {
+                if let Some(i) = (self.pc + 1..self.len()).find(|&i| self.memory[i] == TARGET as Word) {
+                    self.sp = i;
+                }
+                self.pc_plus(1);
+            },
+            PUSHNZ => {
+                let nz = self.nz as u64;
+                self.sp_minus_one();
+                self.set(0, nz);
+                self.pc_plus(1);
+            },
+            SWAP => {
+                let temp = self.get(0);
+                let next = self.get(1);
+                self.set(0, next);
+                self.set(1, temp);
+                self.pc_plus(1);
+            },
+            PUSH0 => {
+                self.sp_minus_one();
+                self.set(0, 0);
+                self.pc_plus(1);
+                self.nz = false;
+            },
+            ADD | SUB | MUL | XOR | AND | OR => {
+                self.sp_minus_one();
+                let (a, b) = (self.get(2), self.get(1));
+                let x = match op_code {
+                    ADD => a + b,
+                    // a - b + 2^W is a - b modulo 2^W
+                    SUB => a + (1u64 << self.arch_width) - b,
+                    MUL => a * b,
+                    XOR => a ^ b,
+                    AND => a & b,
+                    _ => a | b,
+                };
+                self.set(0, x);
+                self.pc_plus(1);
+                self.nz = self.get(0) != 0;
+            },
+            INC | DEC | SHL | SHR | NOT => {
+                let a = self.get(0);
+                let x = match op_code {
+                    INC => a + 1,
+                    DEC => a + (1u64 << self.arch_width) - 1,
+                    SHL => a << 1,
+                    SHR => a >> 1,
+                    _ => !a,
+                };
+                self.set(0, x);
+                self.pc_plus(1);
+                self.nz = self.get(0) != 0;
+            },
+            DIV => {
+                let (mut op0, mut op1) = (self.get(1), self.get(0));
+                if op1 == 0 {
+                    op0 = (1u64 << self.arch_width) - 1;
+                    op1 = 1;
+                }
+                let quotient = self.trunc(op0 / op1);
+                self.set(1, quotient as u64);
+                self.set(0, op0 % op1);
+                self.pc_plus(1);
+                // the remainder overwrites the quotient when L = 1
+                self.nz = quotient != 0;
+            },
+            BZ => { let taken = !self.nz; self.branch_if(taken) },
+            BNZ => { let taken = self.nz; self.branch_if(taken) },
+            BEQ => { let taken = self.get(1) == self.get(0); self.branch_if(taken) },
+            BGT => { let taken = self.get(1) > self.get(0); self.branch_if(taken) },
+            BLT => { let taken = self.get(1) < self.get(0); self.branch_if(taken) },
+            BGE => { let taken = self.get(1) >= self.get(0); self.branch_if(taken) },
+            ENDL => {
+                match (0..self.pc).rev().find(|&i| self.memory[i] == LOOP as Word) {
+                    Some(i) => self.pc = i + 1,
+                    None => self.pc_plus(1),
+                }
+            },
+            BRAN => {
+                match (self.pc + 1..self.len()).find(|&i| self.memory[i] == TARGET as Word) {
+                    Some(i) => self.pc = (i + 1) % self.len(),
+                    None => self.pc_plus(1),
+                }
+            },
+            BRAP => {
+                match (0..self.pc).rev().find(|&i| self.memory[i] == TARGET as Word) {
+                    Some(i) => self.pc = i + 1,
+                    None => self.pc_plus(1),
+                }
+            },
+            SKIP1..=SKIP9 => {
+                let n = (op_code - SKIP1 + 1) as usize;
+                self.pc_plus(n + 1);
+            },
+            #[cfg(feature = "extended-isa")]
+            BLE => { let taken = self.get(1) <= self.get(0); self.branch_if(taken) },
+            #[cfg(feature = "extended-isa")]
+            BNE => { let taken = self.get(1) != self.get(0); self.branch_if(taken) },
+            #[cfg(feature = "extended-isa")]
+            BSLT => {
+                let taken = self.signed(self.get(1) as Word) < self.signed(self.get(0) as Word);
+                self.branch_if(taken)
+            },
+            #[cfg(feature = "extended-isa")]
+            BSGT => {
+                let taken = self.signed(self.get(1) as Word) > self.signed(self.get(0) as Word);
+                self.branch_if(taken)
+            },
+            #[cfg(feature = "extended-isa")]
+            ROT => {
+                let temp = self.get(2);
+                let (second, top) = (self.get(1), self.get(0));
+                self.set(2, second);
+                self.set(1, top);
+                self.set(0, temp);
+                self.pc_plus(1);
+            },
+            #[cfg(feature = "extended-isa")]
+            OVER => {
+                let temp = self.get(1);
+                self.sp_minus_one();
+                self.set(0, temp);
+                self.pc_plus(1);
+                self.nz = temp != 0;
+            },
+            #[cfg(feature = "extended-isa")]
+            MOD => {
+                self.sp_minus_one();
+                let (a, b) = (self.get(2), self.get(1));
+                self.set(0, if b == 0 { 0 } else { a % b });
+                self.pc_plus(1);
+                self.nz = self.get(0) != 0;
+            },
+            // NOP, LOOP, TARGET and every unassigned op code
+            _ => self.pc_plus(1),
+        }
+        status
+    }
+}
+
+impl Engine for Reference {
+    fn load_state(&mut self, state: &MachineState) {
+        self.memory.copy_from_slice(&state.memory);
+        self.pc = state.pc;
+        self.sp = state.sp;
+        self.nz = state.nz;
+    }
+
+    fn step_io(&mut self, io: &mut dyn MachineIo) -> ExecutionSucceeded {
+        self.step(io)
+    }
+
+    fn state(&self) -> MachineState {
+        MachineState { memory: self.memory.clone(), pc: self.pc, sp: self.sp, nz: self.nz }
+    }
+}
+
+/// The first step after which the reference and an engine disagree.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub arch_width: usize,
+    /// The state both machines started from.
+    pub start: MachineState,
+    pub input: Vec<Word>,
+    /// The number of steps executed by both machines, the last one diverged.
+    pub steps: usize,
+    pub reference: MachineState,
+    pub reference_status: ExecutionSucceeded,
+    pub reference_output: Vec<Word>,
+    pub engine: MachineState,
+    pub engine_status: ExecutionSucceeded,
+    pub engine_output: Vec<Word>,
+}
+
+fn write_state(f: &mut fmt::Formatter, name: &str, state: &MachineState) -> fmt::Result {
+    writeln!(f, "{}: pc = {}, sp = {}, nz = {}", name, state.pc, state.sp, state.nz)?;
+    writeln!(f, "    memory: {:?}", state.memory)
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "divergence at step {} with L = {}, W = {}", self.steps, self.start.memory.len(), self.arch_width)?;
+        write_state(f, "start", &self.start)?;
+        writeln!(f, "    input: {:?}", self.input)?;
+        write_state(f, "reference", &self.reference)?;
+        writeln!(f, "    status: {}, output: {:?}", self.reference_status, self.reference_output)?;
+        write_state(f, "engine", &self.engine)?;
+        write!(f, "    status: {}, output: {:?}", self.engine_status, self.engine_output)
+    }
+}
+
+/// Run the reference and an engine in lockstep from the same state and input,
+/// stop at the first step after which their status, state or output differ.
+///
+/// Both machines are stopped after `steps` steps or when they both `HALT`.
+pub fn lockstep<E: Engine>(engine: &mut E, arch_width: usize, start: &MachineState, input: &[Word], steps: usize)
+    -> Option<Divergence> {
+    let mut reference = Reference::new(start.memory.len(), arch_width).expect("invalid machine rank");
+    reference.load_state(start);
+    engine.load_state(start);
+
+    let mut reference_io = QueueIo::new(input.iter().cloned());
+    let mut engine_io = QueueIo::new(input.iter().cloned());
+    for step in 1..=steps {
+        let halting = reference.memory[reference.pc] == HALT as Word;
+        let reference_status = reference.step(&mut reference_io);
+        let engine_status = engine.step_io(&mut engine_io);
+        let (reference_state, engine_state) = (reference.state(), engine.state());
+
+        if reference_status != engine_status || reference_state != engine_state
+            || reference_io.output != engine_io.output {
+            return Some(Divergence {
+                arch_width,
+                start: start.clone(),
+                input: input.to_vec(),
+                steps: step,
+                reference: reference_state,
+                reference_status,
+                reference_output: reference_io.output,
+                engine: engine_state,
+                engine_status,
+                engine_output: engine_io.output,
+            })
+        }
+        if halting {
+            break
+        }
+    }
+    None
+}
+
+const WIDTHS: [usize; 5] = [6, 8, 12, 16, 32];
+
+/// Run `programs` random programs and inputs for up to `steps` steps each,
+/// on the reference and on engines created by `new_engine(arch_length, arch_width)`.
+///
+/// The same `seed` always gives the same programs, return the first divergence.
+pub fn fuzz<E, F>(seed: u32, programs: usize, steps: usize, mut new_engine: F) -> Option<Divergence>
+    where E: Engine, F: FnMut(usize, usize) -> E {
+    let mut rng = RandomPort::new(seed);
+    let mut random = |n: usize| rng.read(0) as usize % n;
+
+    for _ in 0..programs {
+        let arch_length = 1 + random(64);
+        let arch_width = WIDTHS[random(WIDTHS.len())];
+        let max_word = (1u64 << arch_width) - 1;
+
+        // mostly valid op codes, some arbitrary words
+        let memory = (0..arch_length).map(|_| {
+            if random(8) == 0 {
+                (random(usize::MAX) as u64 & max_word) as Word
+            } else {
+                INSTRUCTIONS[random(INSTRUCTIONS.len())].op_code as Word
+            }
+        }).collect();
+        let start = MachineState {
+            memory,
+            pc: random(arch_length),
+            sp: random(arch_length),
+            nz: random(2) == 1,
+        };
+        let input: Vec<_> = (0..random(16)).map(|_| (random(usize::MAX) as u64 & max_word) as Word).collect();
+
+        let mut engine = new_engine(arch_length, arch_width);
+        if let Some(divergence) = lockstep(&mut engine, arch_width, &start, &input, steps) {
+            return Some(divergence)
+        }
+    }
+    None
+}
@@ -0,0 +1,119 @@
+use crate::instruction::op_codes::OpCode;
+use crate::memory::Word;
+
+/// How a memory location has been used since the program was loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CellAccess {
+    /// The location was executed by the PC.
+    pub executed: bool,
+    /// The location was read through the SP.
+    pub read: bool,
+    /// The location was written through the SP.
+    pub written: bool,
+}
+
+impl CellAccess {
+    /// The location was executed, it is code.
+    pub fn is_code(&self) -> bool {
+        self.executed
+    }
+
+    /// The location was touched through the SP, it is data.
+    pub fn is_data(&self) -> bool {
+        self.read || self.written
+    }
+}
+
+/// What happened to a memory location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryEventKind {
+    /// The stack wrote over a location that was executed before.
+    StackOverwroteCode { old: Word, new: Word },
+    /// A location written through the stack since it was last executed is now executed.
+    ModifiedCodeExecuted { op_code: OpCode },
+}
+
+/// A self-modification or stack/code collision event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEvent {
+    /// The address of the location concerned.
+    pub addr: usize,
+    /// The PC of the instruction that triggered the event.
+    pub pc: usize,
+    pub kind: MemoryEventKind,
+}
+
+/// Classify each memory location as code or data and collect
+/// the events where the stack and the code collide.
+#[derive(Debug, Clone)]
+pub struct MemoryWatcher {
+    cells: Vec<CellAccess>,
+    // written through the SP since last executed
+    dirty: Vec<bool>,
+    events: Vec<MemoryEvent>,
+}
+
+impl MemoryWatcher {
+    /// Create a watcher for a memory of `arch_length` locations.
+    pub fn new(arch_length: usize) -> MemoryWatcher {
+        MemoryWatcher {
+            cells: vec![CellAccess::default(); arch_length],
+            dirty: vec![false; arch_length],
+            events: Vec::new(),
+        }
+    }
+
+    /// Return how the location at `addr` has been used.
+    pub fn access(&self, addr: usize) -> CellAccess {
+        self.cells[addr]
+    }
+
+    /// Return how every location has been used.
+    pub fn cells(&self) -> &[CellAccess] {
+        &self.cells
+    }
+
+    /// Return the events not yet drained.
+    pub fn events(&self) -> &[MemoryEvent] {
+        &self.events
+    }
+
+    /// Take the events collected since the last call.
+    pub fn drain_events(&mut self) -> Vec<MemoryEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Forget every access and event, used when a new program is loaded.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = CellAccess::default();
+        }
+        for dirty in &mut self.dirty {
+            *dirty = false;
+        }
+        self.events.clear();
+    }
+
+    pub(crate) fn on_execute(&mut self, pc: usize, op_code: OpCode) {
+        if self.dirty[pc] {
+            let kind = MemoryEventKind::ModifiedCodeExecuted { op_code };
+            self.events.push(MemoryEvent { addr: pc, pc, kind });
+            self.dirty[pc] = false;
+        }
+        self.cells[pc].executed = true;
+    }
+
+    pub(crate) fn on_read(&mut self, addr: usize) {
+        self.cells[addr].read = true;
+    }
+
+    pub(crate) fn on_write(&mut self, pc: usize, addr: usize, old: Word, new: Word) {
+        let cell = &mut self.cells[addr];
+        if cell.executed {
+            let kind = MemoryEventKind::StackOverwroteCode { old, new };
+            self.events.push(MemoryEvent { addr, pc, kind });
+        }
+        cell.written = true;
+        self.dirty[addr] = true;
+    }
+}
@@ -0,0 +1,142 @@
+extern crate reustmann;
+
+use reustmann::analysis::{ControlFlowGraph, Edge, EdgeKind};
+use reustmann::{Dialect, Interpreter, Program};
+
+fn graph(source: &[u8]) -> ControlFlowGraph {
+    graph_with(source, &Dialect::default())
+}
+
+fn graph_with(source: &[u8], dialect: &Dialect) -> ControlFlowGraph {
+    let program = Program::from_iter(source.iter().cloned());
+    let mut interpreter = Interpreter::new(source.len(), 8).unwrap();
+    interpreter.copy_program(&program);
+    ControlFlowGraph::with_dialect(interpreter.memory(), dialect)
+}
+
+fn edge(from: usize, to: usize, kind: EdgeKind) -> Edge {
+    Edge { from, to: Some(to), kind, wrapped: false }
+}
+
+fn wrapped(from: usize, to: usize, kind: EdgeKind) -> Edge {
+    Edge { from, to: Some(to), kind, wrapped: true }
+}
+
+#[test]
+fn fallthrough() {
+    let cfg = graph(b".;H");
+    assert_eq!(cfg.successors(0), [edge(0, 1, EdgeKind::Fallthrough)]);
+    assert_eq!(cfg.successors(1), [edge(1, 2, EdgeKind::Fallthrough)]);
+    assert_eq!(cfg.successors(2), []);
+}
+
+#[test]
+fn skips() {
+    for n in 1..10 {
+        let mut source = vec![b'0' + n as u8];
+        source.extend_from_slice(b";;;;;;;;;;H");
+        let cfg = graph(&source);
+        assert_eq!(cfg.successors(0), [edge(0, n + 1, EdgeKind::Skip)], "SKIP{}", n);
+    }
+}
+
+#[test]
+fn conditional_skip() {
+    let cfg = graph(b"0Z;;H");
+    assert_eq!(cfg.successors(1), [edge(1, 2, EdgeKind::Fallthrough), edge(1, 3, EdgeKind::ConditionalSkip)]);
+}
+
+#[test]
+fn loop_back() {
+    let cfg = graph(b";L.]H");
+    assert_eq!(cfg.successors(3), [edge(3, 2, EdgeKind::LoopBack)]);
+    assert_eq!(cfg.reachable(0), [true, true, true, true, false]);
+}
+
+#[test]
+fn branch_to_nearest_target() {
+    let cfg = graph_with(b"TBTT;b;H", &Dialect::iota());
+    assert_eq!(cfg.successors(1), [edge(1, 3, EdgeKind::Branch)]);
+    assert_eq!(cfg.successors(5), [edge(5, 4, EdgeKind::Branch)]);
+}
+
+#[test]
+fn branch_to_last_target_wraps() {
+    let cfg = graph_with(b"BT", &Dialect::iota());
+    assert_eq!(cfg.successors(0), [wrapped(0, 0, EdgeKind::Branch)]);
+}
+
+#[test]
+fn bran_sets_the_sp() {
+    // the PC stays on the BRAN, BRAP still branches
+    let cfg = graph(b"TBTT;b;H");
+    assert!(Dialect::default().bran_sets_sp);
+    assert_eq!(cfg.successors(1), [edge(1, 1, EdgeKind::Branch)]);
+    assert_eq!(cfg.successors(5), [edge(5, 4, EdgeKind::Branch)]);
+    assert_eq!(cfg.reachable(0), [true, true, false, false, false, false, false, false]);
+
+    // without a TARGET both dialects fall through
+    for dialect in &[Dialect::reustmann(), Dialect::iota()] {
+        let cfg = graph_with(b";B;H", dialect);
+        assert_eq!(cfg.successors(1), [edge(1, 2, EdgeKind::Fallthrough)]);
+    }
+}
+
+#[test]
+fn searches_wrap_around() {
+    let dialect = Dialect { target_search_wraps: true, loop_search_wraps: true, ..Dialect::iota() };
+
+    let cfg = graph_with(b"T;B;H", &dialect);
+    assert_eq!(cfg.successors(2), [wrapped(2, 1, EdgeKind::Branch)]);
+    assert_eq!(graph_with(b"T;B;H", &Dialect::iota()).successors(2), [edge(2, 3, EdgeKind::Fallthrough)]);
+
+    let cfg = graph_with(b"b;T;H", &dialect);
+    assert_eq!(cfg.successors(0), [edge(0, 3, EdgeKind::Branch)]);
+
+    let cfg = graph_with(b"]HL;", &dialect);
+    assert_eq!(cfg.successors(0), [edge(0, 3, EdgeKind::LoopBack)]);
+    assert_eq!(graph_with(b"]HL;", &Dialect::iota()).successors(0), [edge(0, 1, EdgeKind::Fallthrough)]);
+}
+
+#[test]
+fn wraparound() {
+    let cfg = graph(b"..1");
+    assert_eq!(cfg.successors(1), [edge(1, 2, EdgeKind::Fallthrough)]);
+    assert_eq!(cfg.successors(2), [wrapped(2, 1, EdgeKind::Skip)]);
+
+    let cfg = graph(b"0;Z");
+    assert_eq!(cfg.successors(2), [wrapped(2, 0, EdgeKind::Fallthrough), wrapped(2, 1, EdgeKind::ConditionalSkip)]);
+}
+
+#[test]
+fn pop_pc_is_unknown() {
+    let cfg = graph(b"0cH");
+    assert_eq!(cfg.successors(1), [Edge { from: 1, to: None, kind: EdgeKind::Indirect, wrapped: false }]);
+    assert_eq!(cfg.reachable(0), [true, true, false]);
+}
+
+#[test]
+fn reset_goes_to_zero() {
+    let cfg = graph(b";.R");
+    assert_eq!(cfg.successors(2), [edge(2, 0, EdgeKind::Reset)]);
+}
+
+#[test]
+fn dot_output() {
+    let cfg = graph(b"Zc.RH");
+    assert_eq!(cfg.to_dot(), r#"digraph reustmann {
+    node [shape=box, fontname="monospace"];
+    n0 [label="0x0000: Bz"];
+    n1 [label="0x0001: PopPc"];
+    n2 [label="0x0002: Inc"];
+    n3 [label="0x0003: Reset"];
+    n4 [label="0x0004: Halt", color=grey, fontcolor=grey];
+    unknown [label="?", shape=circle];
+    n0 -> n1;
+    n0 -> n2 [label="taken", style=dashed];
+    n1 -> unknown [label="pop pc", style=dotted, color=red];
+    n2 -> n3;
+    n3 -> n0 [label="reset", color=red];
+}
+"#);
+}
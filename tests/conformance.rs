@@ -0,0 +1,118 @@
+extern crate reustmann;
+
+use reustmann::conformance::{self, Case};
+use reustmann::instruction::INSTRUCTIONS;
+use reustmann::instruction::op_codes::*;
+use reustmann::{Dialect, Interpreter};
+
+fn check_dialect(cases: &[Case], dialect: Dialect) {
+    let failures = conformance::check(cases, |arch_length, arch_width| {
+        let mut interpreter = Interpreter::new(arch_length, arch_width).unwrap();
+        interpreter.set_dialect(dialect);
+        interpreter
+    });
+    let messages: Vec<_> = failures.iter().map(ToString::to_string).collect();
+    assert!(failures.is_empty(), "\n{}\n", messages.join("\n"));
+}
+
+fn check(cases: &[Case]) {
+    check_dialect(cases, Dialect::iota());
+}
+
+#[test]
+fn reustmann_dialect() {
+    check_dialect(&conformance::reustmann_cases(), Dialect::reustmann());
+}
+
+#[test]
+fn reustmann_dialect_shares_other_cases() {
+    let cases: Vec<_> = conformance::cases().into_iter()
+        .filter(|case| !conformance::REUSTMANN_OP_CODES.contains(&case.op_code))
+        .collect();
+    check_dialect(&cases, Dialect::reustmann());
+}
+
+#[test]
+fn default_dialect_is_reustmann() {
+    let cases = conformance::reustmann_cases();
+    let failures = conformance::check(&cases, |arch_length, arch_width| Interpreter::new(arch_length, arch_width).unwrap());
+    assert!(failures.is_empty(), "{:?}", failures);
+}
+
+#[test]
+fn every_instruction_has_cases() {
+    for info in INSTRUCTIONS.iter() {
+        assert!(!conformance::cases_for(info.op_code).is_empty(), "no case for {}", info.long_mnemonic);
+    }
+}
+
+macro_rules! conformance_tests {
+    ($($name:ident: $op_code:expr,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check(&conformance::cases_for($op_code));
+            }
+        )*
+    }
+}
+
+conformance_tests! {
+    nop: NOP,
+    reset: RESET,
+    halt: HALT,
+    input: IN,
+    output: OUT,
+    pop: POP,
+    dup: DUP,
+    push_pc: PUSHPC,
+    pop_pc: POPPC,
+    pop_sp: POPSP,
+    sp_target: SPTGT,
+    push_nz: PUSHNZ,
+    swap: SWAP,
+    push0: PUSH0,
+    add: ADD,
+    sub: SUB,
+    inc: INC,
+    dec: DEC,
+    mul: MUL,
+    div: DIV,
+    xor: XOR,
+    and: AND,
+    or: OR,
+    shl: SHL,
+    shr: SHR,
+    not: NOT,
+    bz: BZ,
+    bnz: BNZ,
+    beq: BEQ,
+    bgt: BGT,
+    blt: BLT,
+    bge: BGE,
+    loop_: LOOP,
+    end_loop: ENDL,
+    branch_next: BRAN,
+    branch_previous: BRAP,
+    target: TARGET,
+    skip1: SKIP1,
+    skip2: SKIP2,
+    skip3: SKIP3,
+    skip4: SKIP4,
+    skip5: SKIP5,
+    skip6: SKIP6,
+    skip7: SKIP7,
+    skip8: SKIP8,
+    skip9: SKIP9,
+}
+
+#[cfg(feature = "extended-isa")]
+conformance_tests! {
+    ble: BLE,
+    bne: BNE,
+    rot: ROT,
+    over: OVER,
+    modulo: MOD,
+    bslt: BSLT,
+    bsgt: BSGT,
+}
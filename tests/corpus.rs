@@ -0,0 +1,42 @@
+extern crate reustmann;
+
+use reustmann::corpus::{self, Expectation, Mismatch};
+
+fn assert_no_mismatch(mismatches: &[Mismatch]) {
+    let messages: Vec<_> = mismatches.iter().map(ToString::to_string).collect();
+    assert!(mismatches.is_empty(), "\n{}\n", messages.join("\n"));
+}
+
+#[test]
+fn misc_programs() {
+    let mismatches = corpus::check_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/misc")).unwrap();
+    assert_no_mismatch(&mismatches);
+}
+
+#[test]
+fn echo_iota() {
+    let mismatches = corpus::check_program(concat!(env!("CARGO_MANIFEST_DIR"), "/echo.iota")).unwrap();
+    assert_no_mismatch(&mismatches);
+}
+
+#[test]
+fn wrong_output_is_reported() {
+    let expectation: Expectation = "output: \"Hello\"\ncycles: 10".parse().unwrap();
+    let messages = corpus::run(b"H", &expectation);
+    assert_eq!(messages, vec!["output is \"\", expected \"Hello\"".to_string()]);
+}
+
+#[test]
+fn expectation_escapes() {
+    let expectation: Expectation = r#"input: "a\n\t\0\\\"\x7f""#.parse().unwrap();
+    assert_eq!(expectation.input, b"a\n\t\0\\\"\x7f");
+    assert_eq!(corpus::quote(&expectation.input), r#""a\n\t\0\\\"\x7f""#);
+}
+
+#[test]
+fn invalid_expectation() {
+    assert!("cycles: many".parse::<Expectation>().is_err());
+    assert!("rank: 10".parse::<Expectation>().is_err());
+    assert!("unknown: 1".parse::<Expectation>().is_err());
+    assert!("input: \"\\q\"".parse::<Expectation>().is_err());
+}
@@ -0,0 +1,102 @@
+extern crate reustmann;
+
+use std::cell::RefCell;
+use std::io::{empty, sink};
+use std::rc::Rc;
+
+use reustmann::device::{CycleCounter, Framebuffer, RandomPort};
+use reustmann::instruction::op_codes::HALT;
+use reustmann::{Device, Interpreter, Program, Statement, Word};
+
+fn interpreter(arch_length: usize, source: &[u8]) -> Interpreter {
+    let mut interpreter = Interpreter::new(arch_length, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    interpreter
+}
+
+// always reads the same word
+struct Constant(Word);
+
+impl Device for Constant {
+    fn read(&mut self, _offset: usize) -> Word {
+        self.0
+    }
+
+    fn write(&mut self, _offset: usize, _value: Word) {}
+}
+
+#[test]
+fn cycle_counter_through_the_sp() {
+    // the PUSH0 resets the counter
+    let mut interpreter = interpreter(8, b"0;;O");
+    let counter = Rc::new(RefCell::new(CycleCounter::new()));
+    interpreter.map_device(7, 1, counter.clone()).unwrap();
+
+    let mut output = Vec::new();
+    for _ in 0..4 {
+        interpreter.step(&mut empty(), &mut output);
+    }
+    assert_eq!(output, [3]);
+    assert_eq!(counter.borrow().cycles(), 4);
+}
+
+#[test]
+fn random_port_reads_are_truncated() {
+    // each IN seeds the port with the same word
+    let mut interpreter = interpreter(8, b"IOIO");
+    interpreter.map_device(7, 1, RandomPort::new(0)).unwrap();
+
+    let mut output = Vec::new();
+    for _ in 0..4 {
+        interpreter.step(&mut &[42, 42][..], &mut output);
+    }
+
+    let expected = RandomPort::new(42).read(0) as u8;
+    assert_eq!(output, [expected, expected]);
+}
+
+#[test]
+fn device_executed_through_the_pc() {
+    let mut interpreter = interpreter(8, b";");
+    interpreter.map_device(1, 1, Constant(0x100 | HALT as Word)).unwrap();
+
+    interpreter.step(&mut empty(), &mut sink());
+    let Statement(op_code, _) = interpreter.step(&mut empty(), &mut sink());
+    assert_eq!(op_code, HALT);
+    assert_eq!(interpreter.debug_infos().pc, 1);
+}
+
+#[test]
+fn framebuffer_written_through_the_sp() {
+    let mut interpreter = interpreter(16, b"0.0..H");
+    let framebuffer = Rc::new(RefCell::new(Framebuffer::new(2, 2)));
+    interpreter.map_device(12, 4, framebuffer.clone()).unwrap();
+
+    for _ in 0..6 {
+        interpreter.step(&mut empty(), &mut sink());
+    }
+    assert_eq!(framebuffer.borrow().pixels(), [0, 0, 2, 1]);
+
+    let mut ppm = Vec::new();
+    framebuffer.borrow().write_ppm(&mut ppm).unwrap();
+    assert_eq!(ppm, b"P6\n2 2\n255\n\0\0\0\0\0\0\x02\x02\x02\x01\x01\x01");
+}
+
+#[test]
+fn mapping_errors() {
+    let mut interpreter = interpreter(8, b"");
+    assert!(interpreter.map_device(2, 0, CycleCounter::new()).is_err());
+    assert!(interpreter.map_device(8, 1, CycleCounter::new()).is_err());
+    assert!(interpreter.map_device(6, 3, CycleCounter::new()).is_err());
+
+    interpreter.map_device(2, 4, CycleCounter::new()).unwrap();
+    assert_eq!(interpreter.map_device(5, 2, CycleCounter::new()),
+               Err("Device range overlaps an already mapped device"));
+    assert!(interpreter.map_device(0, 3, CycleCounter::new()).is_err());
+    interpreter.map_device(6, 2, CycleCounter::new()).unwrap();
+    assert_eq!(interpreter.device_ranges().collect::<Vec<_>>(), [(2, 4), (6, 2)]);
+
+    assert!(interpreter.unmap_device(2).is_some());
+    assert!(interpreter.unmap_device(2).is_none());
+    interpreter.map_device(5, 1, CycleCounter::new()).unwrap();
+}
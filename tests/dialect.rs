@@ -0,0 +1,115 @@
+extern crate reustmann;
+
+use reustmann::dialect::{DivByZero, DivResult};
+use reustmann::{DebugInfos, Dialect, Interpreter, Program};
+
+fn run(dialect: Dialect, source: &[u8], input: &[u8], steps: usize) -> DebugInfos {
+    let mut interpreter = Interpreter::new(source.len().max(8), 8).unwrap();
+    interpreter.set_dialect(dialect);
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    let mut input = input;
+    for _ in 0..steps {
+        interpreter.step(&mut input, &mut Vec::new());
+    }
+    interpreter.debug_infos()
+}
+
+fn wrapping(dialect: Dialect) -> Dialect {
+    Dialect { target_search_wraps: true, loop_search_wraps: true, ..dialect }
+}
+
+fn zero_div(dialect: Dialect) -> Dialect {
+    Dialect { div_by_zero: DivByZero::Zero, ..dialect }
+}
+
+#[test]
+fn target_search_wraps() {
+    // BRAP at 0 finds the TARGET at 3 from the other end
+    let program = b"b;;TH;;;";
+    assert_eq!(run(Dialect::iota(), program, b"", 1).pc, 1);
+    assert_eq!(run(wrapping(Dialect::iota()), program, b"", 1).pc, 4);
+
+    // BRAN at 2 finds the TARGET at 0 from the other end
+    let program = b"T;B;H;;;";
+    assert_eq!(run(Dialect::iota(), program, b"", 3).pc, 3);
+    assert_eq!(run(wrapping(Dialect::iota()), program, b"", 3).pc, 1);
+
+    // a BRAN that sets the SP and a SPTGT wrap around the same way
+    let program = b";T;B;G;;";
+    let debug_infos = run(wrapping(Dialect::reustmann()), program, b"", 4);
+    assert_eq!((debug_infos.pc, debug_infos.sp), (3, 1));
+    let debug_infos = run(Dialect::reustmann(), program, b"", 4);
+    assert_eq!((debug_infos.pc, debug_infos.sp), (4, 0));
+    let program = b";T;;G;;;";
+    assert_eq!(run(Dialect::reustmann(), program, b"", 5).sp, 0);
+    assert_eq!(run(wrapping(Dialect::reustmann()), program, b"", 5).sp, 1);
+}
+
+#[test]
+fn loop_search_wraps() {
+    // ENDL at 0 finds the LOOP at 2 from the other end
+    let program = b"]HL;;;;;";
+    assert_eq!(run(Dialect::reustmann(), program, b"", 1).pc, 1);
+    assert_eq!(run(wrapping(Dialect::reustmann()), program, b"", 1).pc, 3);
+}
+
+#[test]
+fn div_by_zero() {
+    // the quotient is pushed over the operands
+    let debug_infos = run(Dialect::reustmann(), b"II/", &[7, 0], 3);
+    assert_eq!((debug_infos.sp, debug_infos.memory[5], debug_infos.nz), (5, 0xff, true));
+    let debug_infos = run(zero_div(Dialect::reustmann()), b"II/", &[7, 0], 3);
+    assert_eq!((debug_infos.sp, debug_infos.memory[5], debug_infos.nz), (5, 0, false));
+
+    // the quotient and the remainder replace the operands
+    assert_eq!(Dialect::iota().div_result, DivResult::QuotientRemainder);
+    let debug_infos = run(Dialect::iota(), b"II/", &[7, 0], 3);
+    assert_eq!((debug_infos.sp, &debug_infos.memory[6..], debug_infos.nz), (6, &[0, 0xff][..], true));
+    let debug_infos = run(zero_div(Dialect::iota()), b"II/", &[7, 0], 3);
+    assert_eq!((debug_infos.sp, &debug_infos.memory[6..], debug_infos.nz), (6, &[0, 0][..], false));
+}
+
+#[test]
+fn custom_dialect_has_no_name() {
+    assert_eq!(Dialect::default().name(), Some("reustmann"));
+    assert_eq!(Dialect::iota().name(), Some("iota"));
+    let custom = zero_div(Dialect::iota());
+    assert_eq!(custom.name(), None);
+    assert!(custom.to_string().starts_with("custom (div: quotient and remainder, div by zero: zero,"));
+}
+
+#[test]
+fn parse_choices() {
+    assert_eq!("iota".parse(), Ok(Dialect::iota()));
+    let dialect: Dialect = "iota,div-by-zero=zero,target-search-wraps=yes".parse().unwrap();
+    assert_eq!(dialect, Dialect { target_search_wraps: true, ..zero_div(Dialect::iota()) });
+    let dialect: Dialect = "reustmann,div=quotient-remainder,bran-sets-sp=no,push-sets-nz=no".parse().unwrap();
+    assert_eq!(dialect, Dialect::iota());
+
+    assert!("custom".parse::<Dialect>().is_err());
+    assert!("iota,div-by-zero".parse::<Dialect>().is_err());
+    assert!("iota,div-by-zero=one".parse::<Dialect>().is_err());
+    assert!("iota,loop-search-wraps=true".parse::<Dialect>().is_err());
+    assert!("iota,wraps=yes".parse::<Dialect>().is_err());
+}
+
+#[test]
+fn spec_parses_back() {
+    assert_eq!(Dialect::reustmann().spec(), "reustmann");
+    assert_eq!(Dialect::iota().spec(), "iota");
+    assert_eq!(zero_div(Dialect::iota()).spec(), "iota,div-by-zero=zero");
+    let dialect = Dialect { bran_sets_sp: false, loop_search_wraps: true, ..Dialect::reustmann() };
+    assert_eq!(dialect.spec(), "reustmann,loop-search-wraps=yes,bran-sets-sp=no");
+
+    for bits in 0..64u8 {
+        let dialect = Dialect {
+            div_result: if bits & 1 != 0 { DivResult::QuotientRemainder } else { DivResult::Quotient },
+            div_by_zero: if bits & 2 != 0 { DivByZero::Zero } else { DivByZero::MaxWord },
+            target_search_wraps: bits & 4 != 0,
+            loop_search_wraps: bits & 8 != 0,
+            bran_sets_sp: bits & 16 != 0,
+            push_sets_nz: bits & 32 != 0,
+        };
+        assert_eq!(dialect.spec().parse(), Ok(dialect), "{}", dialect.spec());
+    }
+}
@@ -0,0 +1,92 @@
+extern crate reustmann;
+
+use reustmann::instruction::op_codes::*;
+use reustmann::{Interpreter, Program, Word};
+
+const TEXT: &[u8] = b"#$%@";
+
+fn interpreter(arch_width: usize, source: &[u8]) -> Interpreter {
+    let mut interpreter = Interpreter::new(16, arch_width).unwrap();
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    interpreter
+}
+
+#[cfg(feature = "extended-isa")]
+fn run(arch_width: usize, source: &[u8], input: &[u8]) -> Interpreter {
+    let mut interpreter = interpreter(arch_width, source);
+    let mut input = input;
+    for _ in 0..source.len() {
+        interpreter.step(&mut input, &mut Vec::new());
+    }
+    interpreter
+}
+
+#[cfg(not(feature = "extended-isa"))]
+#[test]
+fn text_is_unchanged_without_the_feature() {
+    let interpreter = interpreter(8, TEXT);
+    assert_eq!(interpreter.memory()[..4], [BRAP as Word, TARGET as Word, SKIP1 as Word, b'@' as Word]);
+}
+
+#[cfg(feature = "extended-isa")]
+#[test]
+fn text_is_extended_with_the_feature() {
+    let interpreter = interpreter(8, TEXT);
+    assert_eq!(interpreter.memory()[..4], [BNE as Word, OVER as Word, MOD as Word, ROT as Word]);
+}
+
+#[cfg(feature = "extended-isa")]
+fn branch_taken(arch_width: usize, op_code: u8, a: u8, b: u8) -> bool {
+    // the condition compares the second word `a` to the top word `b`
+    let interpreter = run(arch_width, &[b'I', b'I', op_code], &[a, b]);
+    interpreter.debug_infos().pc == 4
+}
+
+#[cfg(feature = "extended-isa")]
+#[test]
+fn signed_compares_at_the_sign_boundary() {
+    // 0x7f is the greatest positive word and 0x80 the lowest negative one
+    assert!(!branch_taken(8, b'?', 0x7f, 0x80));
+    assert!(branch_taken(8, b'`', 0x7f, 0x80));
+    assert!(branch_taken(8, b'?', 0x80, 0x7f));
+    assert!(!branch_taken(8, b'`', 0x80, 0x7f));
+    assert!(branch_taken(8, b'?', 0xff, 0x00));
+
+    // the sign bit is the W-th bit, the inputs are truncated to 6 bits
+    assert!(!branch_taken(6, b'?', 0x1f, 0x20));
+    assert!(branch_taken(6, b'`', 0x1f, 0x20));
+    assert!(branch_taken(6, b'`', 0x5f, 0x60));
+    assert!(branch_taken(6, b'?', 0x3f, 0x00));
+
+    // unsigned compares disagree at the boundary
+    assert!(branch_taken(8, b'<', 0x7f, 0x80));
+    assert!(!branch_taken(8, b'<', 0x80, 0x7f));
+}
+
+#[cfg(feature = "extended-isa")]
+#[test]
+fn mod_by_zero_pushes_zero() {
+    let interpreter = run(8, b"II%", &[7, 3]);
+    let debug_infos = interpreter.debug_infos();
+    assert_eq!((debug_infos.sp, debug_infos.memory[13], debug_infos.nz), (13, 1, true));
+
+    let interpreter = run(8, b"II%", &[7, 0]);
+    let debug_infos = interpreter.debug_infos();
+    assert_eq!((debug_infos.sp, debug_infos.memory[13], debug_infos.nz), (13, 0, false));
+}
+
+#[cfg(feature = "extended-isa")]
+#[test]
+fn rot_and_over_stack_effects() {
+    // ROT moves the third word on top: 1 2 3 -> 2 3 1
+    let interpreter = run(8, b"III@", &[1, 2, 3]);
+    let debug_infos = interpreter.debug_infos();
+    assert_eq!(debug_infos.sp, 13);
+    assert_eq!(debug_infos.memory[13..], [1, 3, 2]);
+
+    // OVER pushes a copy of the second word: 1 2 -> 1 2 1
+    let interpreter = run(8, b"II$", &[1, 2]);
+    let debug_infos = interpreter.debug_infos();
+    assert_eq!(debug_infos.sp, 13);
+    assert_eq!(debug_infos.memory[13..], [1, 2, 1]);
+}
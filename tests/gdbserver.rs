@@ -0,0 +1,154 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, Stdio};
+
+/// A client of the remote serial protocol.
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn request(&mut self, data: &str) -> String {
+        let checksum = data.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        write!(self.stream, "${}#{:02x}", data, checksum).unwrap();
+
+        let mut ack = [0];
+        self.stream.read_exact(&mut ack).unwrap();
+        assert_eq!(ack[0], b'+', "packet {:?} not acknowledged", data);
+
+        let mut reply = Vec::new();
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], b'$');
+        loop {
+            self.stream.read_exact(&mut byte).unwrap();
+            if byte[0] == b'#' {
+                break
+            }
+            reply.push(byte[0]);
+        }
+        let mut sum = [0; 2];
+        self.stream.read_exact(&mut sum).unwrap();
+        let expected = reply.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        assert_eq!(std::str::from_utf8(&sum).unwrap(), format!("{:02x}", expected));
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+fn start(program: &str) -> (Child, Client) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_reustmann"))
+        .args(["gdbserver", program, "--port", "0"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stderr.as_mut().unwrap()).read_line(&mut line).unwrap();
+    let addr = line.trim().strip_prefix("Listening on ").unwrap_or_else(|| panic!("unexpected {:?}", line));
+    let stream = TcpStream::connect(addr).unwrap();
+    stream.set_nodelay(true).unwrap();
+    (child, Client { stream })
+}
+
+#[test]
+fn debug_hello_world() {
+    let (mut child, mut client) = start("misc/hello_world.rm");
+
+    assert_eq!(client.request("qSupported:multiprocess+"), "PacketSize=4000;qXfer:features:read+");
+    assert_eq!(client.request("qXfer:features:read:target.xml:0,5"), "m<?xml");
+    assert_eq!(client.request("?"), "S05");
+    assert_eq!(client.request("g"), "000000000000000000000000");
+    assert_eq!(client.request("m0,4"), "0a051010");
+    assert_eq!(client.request("m12,5"), "46656c6c6f");
+    assert_eq!(client.request("m1d,2"), "E01");
+
+    assert_eq!(client.request("Z0,4,1"), "OK");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(client.request("p0"), "04000000");
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("p0"), "05000000");
+
+    assert_eq!(client.request("M13,1:61"), "OK");
+    assert_eq!(client.request("m13,1"), "61");
+    assert_eq!(client.request("P2=01000000"), "OK");
+    assert_eq!(client.request("p2"), "01000000");
+    assert_eq!(client.request("P0=1e000000"), "E01");
+
+    // an invalid SP leaves every register unchanged
+    let registers = client.request("g");
+    assert_eq!(client.request("G030000001e00000000000000"), "E01");
+    assert_eq!(client.request("g"), registers);
+    assert_eq!(client.request("G030000001d00000000000000"), "OK");
+    assert_eq!(client.request("g"), "030000001d00000000000000");
+    assert_eq!(client.request(&format!("G{}", registers)), "OK");
+
+    assert_eq!(client.request("z0,4,1"), "OK");
+    assert_eq!(client.request("c"), "W00");
+    assert_eq!(client.request("s"), "W00");
+    assert_eq!(client.request("D"), "OK");
+
+    let mut output = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut output).unwrap();
+    assert!(child.wait().unwrap().success());
+    assert_eq!(output, "Hallo World!");
+}
+
+#[test]
+fn resume_after_halt() {
+    let (mut child, mut client) = start("misc/hello_world.rm");
+
+    assert_eq!(client.request("c"), "W00");
+    assert_eq!(client.request("p0"), "10000000");
+    assert_eq!(client.request("P2=00000000"), "OK");
+    assert_eq!(client.request("s"), "W00");
+
+    // overwriting the HALT resumes the execution
+    assert_eq!(client.request("M10,1:00"), "OK");
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("p0"), "11000000");
+
+    // so does moving the PC off a HALT
+    assert_eq!(client.request("M10,1:02"), "OK");
+    assert_eq!(client.request("P0=10000000"), "OK");
+    assert_eq!(client.request("s"), "W00");
+    assert_eq!(client.request("P0=00000000"), "OK");
+    assert_eq!(client.request("?"), "S05");
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("p0"), "01000000");
+    assert_eq!(client.request("D"), "OK");
+
+    let mut output = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut output).unwrap();
+    assert!(child.wait().unwrap().success());
+    assert_eq!(output, "Hello World!");
+}
+
+#[test]
+fn interrupt_endless_program() {
+    let (mut child, mut client) = start("misc/little.rm");
+
+    assert_eq!(client.request("Z0,2,1"), "OK");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(client.request("z0,2,1"), "OK");
+
+    // the stop reply of the continue comes once interrupted
+    write!(client.stream, "$c#63").unwrap();
+    let mut ack = [0];
+    client.stream.read_exact(&mut ack).unwrap();
+    assert_eq!(ack[0], b'+');
+    client.stream.write_all(&[0x03]).unwrap();
+    let mut reply = [0; 7];
+    client.stream.read_exact(&mut reply).unwrap();
+    assert_eq!(&reply, b"$S02#b5");
+    client.stream.write_all(b"+").unwrap();
+
+    assert_eq!(client.request("s"), "S05");
+    // a kill has no reply
+    write!(client.stream, "$k#6b").unwrap();
+    client.stream.read_exact(&mut ack).unwrap();
+    assert!(child.wait().unwrap().success());
+}
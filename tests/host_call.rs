@@ -0,0 +1,96 @@
+extern crate reustmann;
+
+use std::io::{empty, sink};
+
+use reustmann::instruction::op_codes::{ADD, NOP};
+use reustmann::{DebugInfos, HostContext, Interpreter, Program, Statement};
+
+const CALL: u8 = b'x';
+
+fn interpreter(source: &[u8]) -> Interpreter {
+    let mut interpreter = Interpreter::new(8, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    interpreter
+}
+
+fn registers(interpreter: &Interpreter) -> (usize, usize, bool) {
+    let DebugInfos { pc, sp, nz, .. } = interpreter.debug_infos();
+    (pc, sp, nz)
+}
+
+#[test]
+fn registered_handler_runs() {
+    let mut interpreter = interpreter(b"xx");
+    interpreter.register_host_call(CALL, |context: &mut HostContext| {
+        assert_eq!(context.pc(), 0);
+        context.push(0x1ff);
+        context.set_nz(true);
+        context.write_word(b'!' as u32).is_ok()
+    }).unwrap();
+
+    let mut output = Vec::new();
+    let Statement(op_code, status) = interpreter.step(&mut empty(), &mut output);
+    assert_eq!((op_code, status), (CALL, true));
+    assert_eq!(output, b"!");
+    assert_eq!(registers(&interpreter), (1, 7, true));
+    // the word pushed is truncated to the arch width
+    assert_eq!(interpreter.memory()[7], 0xff);
+}
+
+#[test]
+fn failing_handler() {
+    let mut interpreter = interpreter(b"x");
+    interpreter.register_host_call(CALL, |context: &mut HostContext| { context.pop(); false }).unwrap();
+
+    let Statement(_, status) = interpreter.step(&mut empty(), &mut sink());
+    assert!(!status);
+    assert_eq!(registers(&interpreter), (1, 1, false));
+}
+
+#[test]
+fn assigned_op_codes_are_refused() {
+    let mut interpreter = interpreter(b"+");
+    let refused = interpreter.register_host_call(ADD, |_: &mut HostContext| true);
+    assert_eq!(refused, Err("Host calls can only be registered for unassigned op codes"));
+    assert!(interpreter.register_host_call(NOP, |_: &mut HostContext| true).is_err());
+}
+
+#[test]
+fn unregistered_op_code_is_a_nop() {
+    let mut interpreter = interpreter(b"xx");
+    interpreter.register_host_call(CALL, |context: &mut HostContext| { context.push(1); true }).unwrap();
+    interpreter.register_host_call(b'y', |_: &mut HostContext| false).unwrap();
+    assert!(interpreter.unregister_host_call(CALL).is_some());
+    assert!(interpreter.unregister_host_call(CALL).is_none());
+
+    let Statement(op_code, status) = interpreter.step(&mut empty(), &mut sink());
+    assert_eq!((op_code, status), (CALL, true));
+    assert_eq!(registers(&interpreter), (1, 0, false));
+    assert_eq!(interpreter.memory()[7], NOP as u32);
+}
+
+#[test]
+fn registering_replaces_the_handler() {
+    let mut interpreter = interpreter(b"x");
+    interpreter.register_host_call(CALL, |_: &mut HostContext| false).unwrap();
+    interpreter.register_host_call(CALL, |_: &mut HostContext| true).unwrap();
+
+    let Statement(_, status) = interpreter.step(&mut empty(), &mut sink());
+    assert!(status);
+}
+
+#[test]
+fn handler_moves_the_pc() {
+    let mut interpreter = interpreter(b";x");
+    interpreter.register_host_call(CALL, |context: &mut HostContext| {
+        let pc = context.pc();
+        // the PC is masked to the arch length
+        context.set_pc(pc + 12);
+        assert_eq!(context.pc(), pc);
+        true
+    }).unwrap();
+
+    interpreter.step(&mut empty(), &mut sink());
+    interpreter.step(&mut empty(), &mut sink());
+    assert_eq!(registers(&interpreter), (5, 0, false));
+}
@@ -0,0 +1,57 @@
+extern crate reustmann;
+
+use std::io::{empty, sink};
+
+use reustmann::{Dialect, Interpreter, StepDetails};
+use reustmann::instruction::{INSTRUCTIONS, InstructionInfo, op_code_info};
+use reustmann::instruction::op_codes::*;
+
+fn step(info: &InstructionInfo, dialect: Dialect, nz: bool) -> (StepDetails, bool) {
+    let mut interpreter = Interpreter::new(16, 8).unwrap();
+    interpreter.set_dialect(dialect);
+    interpreter.poke(0, info.op_code as u32);
+    for (addr, &word) in [3, 5, 7].iter().enumerate() {
+        interpreter.poke(8 + addr, word);
+    }
+    interpreter.set_sp(8);
+    interpreter.set_nz(nz);
+    let details = interpreter.step_detailed(&mut empty(), &mut sink());
+    (details, interpreter.nz())
+}
+
+fn check_table(dialect: Dialect) {
+    for info in INSTRUCTIONS.iter() {
+        let effects = info.effects(&dialect);
+        let (details, nz_after_set) = step(info, dialect, true);
+        let (_, nz_after_clear) = step(info, dialect, false);
+        let name = info.long_mnemonic;
+
+        assert_eq!(details.pushed.is_some(), effects.pushes == 1, "{} pushes in {}", name, dialect);
+        assert_eq!(details.popped.is_some(), effects.pops == 1, "{} pops in {}", name, dialect);
+        // PUSHNZ sets NZ from NZ itself, that can't be observed
+        if info.op_code != PUSHNZ {
+            assert_eq!(nz_after_set == nz_after_clear, effects.sets_nz, "{} sets NZ in {}", name, dialect);
+        }
+    }
+}
+
+#[test]
+fn table_matches_reustmann_dialect() {
+    check_table(Dialect::reustmann());
+}
+
+#[test]
+fn table_matches_iota_dialect() {
+    check_table(Dialect::iota());
+}
+
+#[test]
+fn effects_depend_on_dialect() {
+    let div = op_code_info(DIV).unwrap();
+    assert_eq!(div.effects(&Dialect::reustmann()).pushes, 1);
+    assert_eq!(div.effects(&Dialect::iota()).pushes, 0);
+
+    let push_pc = op_code_info(PUSHPC).unwrap();
+    assert!(push_pc.effects(&Dialect::reustmann()).sets_nz);
+    assert!(!push_pc.effects(&Dialect::iota()).sets_nz);
+}
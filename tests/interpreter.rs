@@ -0,0 +1,32 @@
+extern crate reustmann;
+
+use std::io::{empty, sink};
+
+use reustmann::{Interpreter, Program};
+
+#[test]
+fn pushpc_truncates_the_pc() {
+    // 70 NOPs then a PUSHPC, the PC does not fit in a 6 bits word
+    let mut interpreter = Interpreter::new(80, 6).unwrap();
+    let source = b";".iter().cycle().take(70).chain(b"C").cloned();
+    interpreter.copy_program(&Program::from_iter(source));
+
+    for _ in 0..71 {
+        interpreter.step(&mut empty(), &mut sink());
+    }
+    let debug_infos = interpreter.debug_infos();
+    assert_eq!(debug_infos.sp, 79);
+    assert_eq!(debug_infos.memory[79], 70 & 0x3f);
+}
+
+#[test]
+fn memory_holds_whole_words() {
+    // PUSH0 then DEC, the word wraps around to the maximum of 16 bits
+    let mut interpreter = Interpreter::new(8, 16).unwrap();
+    interpreter.copy_program(&Program::from_iter(b"0,".iter().cloned()));
+
+    interpreter.step(&mut empty(), &mut sink());
+    interpreter.step(&mut empty(), &mut sink());
+    assert_eq!(interpreter.memory()[7], 0xffff);
+    assert_eq!(interpreter.debug_infos().memory[7], 0xffff);
+}
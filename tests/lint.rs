@@ -0,0 +1,120 @@
+extern crate reustmann;
+
+use std::process::Command;
+
+use reustmann::Program;
+use reustmann::analysis::lint;
+
+fn lint_lines(source: &[u8], arch_length: usize, arch_width: usize) -> Vec<String> {
+    let program = Program::from_iter(source.iter().cloned());
+    lint(&program, arch_length, arch_width).iter().map(ToString::to_string).collect()
+}
+
+fn lint_program(source: &[u8]) -> Vec<String> {
+    lint_lines(source, source.len(), 8)
+}
+
+#[test]
+fn endl_without_loop() {
+    assert_eq!(lint_program(b"]H"), ["0: endl-without-loop: ENDL has no preceding LOOP"]);
+    assert_eq!(lint_program(b"L]H"), ["2: unreachable-halt: HALT can never be reached",
+                                      "2: unreachable: 1 unreachable instruction(s)"]);
+}
+
+#[test]
+fn branch_without_target() {
+    assert_eq!(lint_program(b"BH"), ["0: bran-without-target: BRAN has no following TARGET"]);
+    assert_eq!(lint_program(b"bH"), ["0: brap-without-target: BRAP has no preceding TARGET"]);
+    assert_eq!(lint_program(b"TBH"), ["1: bran-without-target: BRAN has no following TARGET"]);
+    assert_eq!(lint_program(b"HbT"), ["1: brap-without-target: BRAP has no preceding TARGET",
+                                      "1: unreachable: 2 unreachable instruction(s)"]);
+}
+
+#[test]
+fn skip_wraps() {
+    assert_eq!(lint_program(b"0Z3H"), ["2: skip-wraps: skip wraps around the end of memory"]);
+    assert_eq!(lint_program(b"0;;Z"), ["3: skip-wraps: skip wraps around the end of memory"]);
+    assert_eq!(lint_program(b"0Z1;H"), Vec::<String>::new());
+}
+
+#[test]
+fn unreachable_instructions() {
+    assert_eq!(lint_program(b"H.."), ["1: unreachable: 2 unreachable instruction(s)"]);
+    assert_eq!(lint_program(b"2..H.."), ["1: unreachable: 2 unreachable instruction(s)",
+                                         "4: unreachable: 2 unreachable instruction(s)"]);
+}
+
+#[test]
+fn unreachable_halt() {
+    assert_eq!(lint_program(b".1HH"), ["2: unreachable-halt: HALT can never be reached",
+                                       "2: unreachable: 1 unreachable instruction(s)"]);
+}
+
+#[test]
+fn pop_pc_makes_everything_reachable() {
+    assert_eq!(lint_program(b"0cH.."), Vec::<String>::new());
+}
+
+#[test]
+fn data_chars() {
+    assert_eq!(lint_program(b"x\x0dH"), ["0: data-as-nop: 0x78 is not a mnemonic and will be executed as NOP",
+                                         "1: data-as-op-code: 0x0d is not a mnemonic but will be executed as Push0"]);
+}
+
+#[test]
+fn data_chars_are_truncated_to_the_arch_width() {
+    // 'e' is 0x65 and 0x65 & 0x3f is 0x25, a SKIP1
+    assert_eq!(lint_lines(b"e;H", 3, 8), ["0: data-as-nop: 0x65 is not a mnemonic and will be executed as NOP"]);
+    assert_eq!(lint_lines(b"e;H", 3, 6), ["0: data-as-op-code: 0x65 is not a mnemonic but will be executed as Skip1",
+                                          "1: unreachable: 1 unreachable instruction(s)"]);
+}
+
+#[test]
+fn unreachable_data_is_reported_once() {
+    assert_eq!(lint_program(b"Hx\x0d"), ["1: unreachable: 2 unreachable instruction(s)"]);
+}
+
+#[test]
+fn truncated_program() {
+    assert_eq!(lint_lines(b"H..", 1, 8), ["1: truncated: 2 char(s) don't fit in memory"]);
+}
+
+fn run_lint(file_name: &str) -> (Option<i32>, String) {
+    run_lint_with(&[file_name])
+}
+
+fn run_lint_with(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_reustmann"))
+        .arg("lint")
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap();
+    (output.status.code(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+fn lint_shipped_programs() {
+    assert_eq!(run_lint("echo.iota"), (Some(0), String::new()));
+    assert_eq!(run_lint("misc/hello_world.rm"),
+               (Some(1), "misc/hello_world.rm:17: unreachable: 13 unreachable instruction(s)\n".to_string()));
+    assert_eq!(run_lint("misc/fibonacci.rm"),
+               (Some(1), "misc/fibonacci.rm:9: unreachable: 23 unreachable instruction(s)\n".to_string()));
+    assert_eq!(run_lint("misc/print_input.rm"),
+               (Some(1), "misc/print_input.rm:6: unreachable: 1 unreachable instruction(s)\n".to_string()));
+    assert_eq!(run_lint("misc/little.rm"), (Some(1), concat!(
+        "misc/little.rm:0: data-as-nop: 0x61 is not a mnemonic and will be executed as NOP\n",
+        "misc/little.rm:1: brap-without-target: BRAP has no preceding TARGET\n",
+        "misc/little.rm:3: data-as-nop: 0x64 is not a mnemonic and will be executed as NOP\n",
+        "misc/little.rm:4: data-as-nop: 0x65 is not a mnemonic and will be executed as NOP\n",
+        "misc/little.rm:5: data-as-nop: 0x66 is not a mnemonic and will be executed as NOP\n").to_string()));
+}
+
+#[test]
+fn lint_for_an_arch_width() {
+    assert_eq!(run_lint_with(&["misc/print_input.rm", "7", "6"]),
+               (Some(1), "misc/print_input.rm:6: unreachable: 1 unreachable instruction(s)\n".to_string()));
+    assert_eq!(run_lint_with(&["misc/little.rm", "6", "6"]).1.lines().nth(1),
+               Some("misc/little.rm:0: data-as-op-code: 0x61 is not a mnemonic but will be executed as EndL"));
+    assert_eq!(run_lint_with(&["misc/little.rm", "6", "33"]), (Some(2), String::new()));
+}
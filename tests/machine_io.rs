@@ -0,0 +1,168 @@
+extern crate reustmann;
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use reustmann::machine_io::{ByteStream, QueueIo, fn_io};
+use reustmann::{Encoding, Interpreter, MachineIo, Program};
+
+// gives its bytes one at a time, interrupted before each of them
+struct Trickle<'a> {
+    bytes: &'a [u8],
+    interrupted: bool,
+}
+
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupted = !self.interrupted;
+        if self.interrupted {
+            return Err(io::Error::new(ErrorKind::Interrupted, "interrupted"))
+        }
+        match self.bytes.split_first() {
+            Some((&b, rest)) if !buf.is_empty() => {
+                buf[0] = b;
+                self.bytes = rest;
+                Ok(1)
+            },
+            _ => Ok(0),
+        }
+    }
+}
+
+// accepts its bytes one at a time
+struct Narrow(Vec<u8>);
+
+impl Write for Narrow {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match buf.first() {
+            Some(&b) => { self.0.push(b); Ok(1) },
+            None => Ok(0),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn trickle(bytes: &[u8]) -> Trickle<'_> {
+    Trickle { bytes, interrupted: false }
+}
+
+#[test]
+fn bytes() {
+    let mut io = ByteStream::new(&b"Hi"[..], Vec::new());
+    assert_eq!(io.read_word().unwrap(), Some(b'H' as u32));
+    assert_eq!(io.read_word().unwrap(), Some(b'i' as u32));
+    assert_eq!(io.read_word().unwrap(), None);
+
+    io.write_word(0x141).unwrap();
+    assert_eq!(io.into_inner().1, b"A");
+}
+
+#[test]
+fn partial_reads_of_wide_words() {
+    let mut io = ByteStream::with_encoding(trickle(&[0x34, 0x12, 0x78, 0x56, 0x34]), Vec::new(),
+                                           Encoding::RawLittleEndian, 24);
+    assert_eq!(io.read_word().unwrap(), Some(0x78_1234));
+    let err = io.read_word().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn partial_writes_of_wide_words() {
+    let mut io = ByteStream::with_encoding(&b""[..], Narrow(Vec::new()), Encoding::RawLittleEndian, 12);
+    io.write_word(0xabc).unwrap();
+    io.write_word(0x123).unwrap();
+    assert_eq!(io.into_inner().1 .0, [0xbc, 0x0a, 0x23, 0x01]);
+}
+
+#[test]
+fn partial_word_is_unexpected_eof() {
+    let mut io = ByteStream::with_encoding(&[0x01, 0x02, 0x03][..], Vec::new(), Encoding::RawLittleEndian, 16);
+    assert_eq!(io.read_word().unwrap(), Some(0x0201));
+    assert_eq!(io.read_word().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn queue_io() {
+    let mut interpreter = Interpreter::new(8, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(b"IOIOH".iter().cloned()));
+    let mut io = QueueIo::new(vec![7, 300]);
+    for _ in 0..5 {
+        interpreter.step_io(&mut io);
+    }
+    // the interpreter truncates the words read to its arch width
+    assert_eq!(io.output, [7, 44]);
+    assert!(io.input.is_empty());
+}
+
+#[test]
+fn closure_io() {
+    let mut input = vec![2, 1];
+    let mut output = Vec::new();
+    {
+        let mut io = fn_io(|| input.pop(), |word| output.push(word));
+        assert_eq!(io.read_word().unwrap(), Some(1));
+        io.write_word(9).unwrap();
+    }
+    assert_eq!(input, [2]);
+    assert_eq!(output, [9]);
+}
+
+#[test]
+fn utf8() {
+    let mut io = ByteStream::with_encoding("é€😀".as_bytes(), Vec::new(), Encoding::Utf8, 32);
+    assert_eq!(io.read_word().unwrap(), Some('é' as u32));
+    assert_eq!(io.read_word().unwrap(), Some('€' as u32));
+    assert_eq!(io.read_word().unwrap(), Some('😀' as u32));
+    assert_eq!(io.read_word().unwrap(), None);
+
+    io.write_word('€' as u32).unwrap();
+    assert_eq!(io.write_word(0xd800).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(io.into_inner().1, "€".as_bytes());
+}
+
+#[test]
+fn invalid_utf8() {
+    let mut io = ByteStream::with_encoding(&[0xff][..], Vec::new(), Encoding::Utf8, 16);
+    assert_eq!(io.read_word().unwrap_err().kind(), ErrorKind::InvalidData);
+
+    let mut io = ByteStream::with_encoding(&[0xe2, 0x82][..], Vec::new(), Encoding::Utf8, 16);
+    assert_eq!(io.read_word().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    let mut io = ByteStream::with_encoding(&[0xc3, 0x28][..], Vec::new(), Encoding::Utf8, 16);
+    assert_eq!(io.read_word().unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn utf16() {
+    let mut io = ByteStream::with_encoding(&[0xac, 0x20, 0x41][..], Vec::new(), Encoding::Utf16, 16);
+    assert_eq!(io.read_word().unwrap(), Some(0x20ac));
+    assert_eq!(io.read_word().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+    io.write_word(0x20ac).unwrap();
+    assert_eq!(io.into_inner().1, [0xac, 0x20]);
+}
+
+#[test]
+fn interpreter_encoding() {
+    let mut interpreter = Interpreter::new(8, 16).unwrap();
+    interpreter.set_encoding(Encoding::Utf8);
+    interpreter.copy_program(&Program::from_iter(b"IOIOH".iter().cloned()));
+
+    let mut input = "é€".as_bytes();
+    let mut output = Vec::new();
+    for _ in 0..5 {
+        interpreter.step(&mut input, &mut output);
+    }
+    assert_eq!(output, "é€".as_bytes());
+}
+
+#[test]
+fn encoding_names() {
+    for &encoding in &[Encoding::Byte, Encoding::Utf8, Encoding::Utf16, Encoding::RawLittleEndian] {
+        assert_eq!(encoding.to_string().parse::<Encoding>(), Ok(encoding));
+    }
+    assert_eq!("utf-8".parse::<Encoding>(), Ok(Encoding::Utf8));
+    assert!("latin1".parse::<Encoding>().is_err());
+}
@@ -0,0 +1,100 @@
+extern crate reustmann;
+
+use reustmann::instruction::op_codes::*;
+use reustmann::{Dialect, Interpreter, Observer, Program, Word};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Event {
+    Step(usize, OpCode),
+    Read(usize, Word),
+    Write(usize, Word, Word),
+    Input(Word, bool),
+    Output(Word, bool),
+    Branch(usize, usize),
+    Halt(usize),
+}
+
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+impl Observer for Recorder {
+    fn on_step(&mut self, pc: usize, op_code: OpCode) {
+        self.0.push(Event::Step(pc, op_code))
+    }
+
+    fn on_memory_read(&mut self, addr: usize, value: Word) {
+        self.0.push(Event::Read(addr, value))
+    }
+
+    fn on_memory_write(&mut self, addr: usize, old: Word, new: Word) {
+        self.0.push(Event::Write(addr, old, new))
+    }
+
+    fn on_input(&mut self, value: Word, succeeded: bool) {
+        self.0.push(Event::Input(value, succeeded))
+    }
+
+    fn on_output(&mut self, value: Word, succeeded: bool) {
+        self.0.push(Event::Output(value, succeeded))
+    }
+
+    fn on_branch(&mut self, from: usize, to: usize) {
+        self.0.push(Event::Branch(from, to))
+    }
+
+    fn on_halt(&mut self, pc: usize) {
+        self.0.push(Event::Halt(pc))
+    }
+}
+
+#[test]
+fn callbacks_order() {
+    let mut interpreter = Interpreter::new(8, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(b"IO1;H".iter().cloned()));
+
+    let mut recorder = Recorder::default();
+    let mut output = Vec::new();
+    for _ in 0..4 {
+        interpreter.step_with(&mut &b"A"[..], &mut output, &mut recorder);
+    }
+
+    assert_eq!(output, b"A");
+    assert_eq!(recorder.0, [
+        Event::Step(0, IN),
+        Event::Write(7, NOP as Word, 65),
+        Event::Input(65, true),
+        Event::Step(1, OUT),
+        Event::Read(7, 65),
+        Event::Output(65, true),
+        Event::Step(2, SKIP1),
+        Event::Branch(2, 4),
+        Event::Step(4, HALT),
+        Event::Halt(4),
+    ]);
+}
+
+#[test]
+fn reset_is_a_branch() {
+    let mut interpreter = Interpreter::new(4, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(b";R".iter().cloned()));
+    interpreter.step(&mut &b""[..], &mut Vec::new());
+
+    let mut recorder = Recorder::default();
+    interpreter.step_with(&mut &b""[..], &mut Vec::new(), &mut recorder);
+    assert_eq!(recorder.0, [Event::Step(1, RESET), Event::Branch(1, 0)]);
+}
+
+#[test]
+fn bran_setting_the_sp_is_not_a_branch() {
+    let mut interpreter = Interpreter::new(4, 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(b"B;T;".iter().cloned()));
+
+    let mut recorder = Recorder::default();
+    interpreter.step_with(&mut &b""[..], &mut Vec::new(), &mut recorder);
+    assert_eq!(recorder.0, [Event::Step(0, BRAN)]);
+
+    interpreter.set_dialect(Dialect::iota());
+    let mut recorder = Recorder::default();
+    interpreter.step_with(&mut &b""[..], &mut Vec::new(), &mut recorder);
+    assert_eq!(recorder.0, [Event::Step(0, BRAN), Event::Branch(0, 3)]);
+}
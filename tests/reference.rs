@@ -0,0 +1,39 @@
+extern crate reustmann;
+
+use reustmann::conformance;
+use reustmann::reference::{self, Reference};
+use reustmann::{Dialect, Interpreter};
+
+fn iota_interpreter(arch_length: usize, arch_width: usize) -> Interpreter {
+    let mut interpreter = Interpreter::new(arch_length, arch_width).unwrap();
+    interpreter.set_dialect(Dialect::iota());
+    interpreter
+}
+
+#[test]
+fn reference_passes_conformance() {
+    let failures = conformance::check(&conformance::cases(), |arch_length, arch_width| {
+        Reference::new(arch_length, arch_width).unwrap()
+    });
+    let messages: Vec<_> = failures.iter().map(ToString::to_string).collect();
+    assert!(failures.is_empty(), "\n{}\n", messages.join("\n"));
+}
+
+#[test]
+fn interpreter_agrees_with_reference() {
+    for seed in 1..=8 {
+        if let Some(divergence) = reference::fuzz(seed, 500, 300, iota_interpreter) {
+            panic!("seed {}: {}", seed, divergence);
+        }
+    }
+}
+
+#[test]
+fn reustmann_dialect_diverges() {
+    let divergence = reference::fuzz(1, 500, 300, |arch_length, arch_width| {
+        Interpreter::new(arch_length, arch_width).unwrap()
+    });
+    let divergence = divergence.expect("the legacy dialect should diverge from the reference");
+    assert!(divergence.reference != divergence.engine
+         || divergence.reference_output != divergence.engine_output);
+}
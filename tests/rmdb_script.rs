@@ -0,0 +1,108 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run_script(script: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_reustmann"))
+        .args(["--script", "-"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn passing_script() {
+    let output = run_script("# comment\n\ncopy misc/hello_world.rm\ncontinue\nassert out == 12 && nz\nprint mem[0x12]\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!stdout.contains('\x1b'), "colors in {:?}", stdout);
+    assert!(stdout.contains("Halted after 16 steps."));
+    assert!(stdout.ends_with("mem[0x12] = 72\n"));
+}
+
+#[test]
+fn failed_assertion() {
+    let output = run_script("copy misc/hello_world.rm\nassert cycles == 1\nprint pc\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "-:2: Assertion failed: cycles == 1\n");
+    assert!(!stdout.contains("pc = "));
+}
+
+#[test]
+fn failed_command() {
+    let output = run_script("step\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "-:1: No interpreter created\n");
+}
+
+#[test]
+fn reverse_execution() {
+    let script = "copy misc/print_input.rm\ninput \"abc\"\ncontinue\n\
+                  rstep 3\nassert cycles == 12 && out == 3\n\
+                  break op O\nrcontinue\nassert cycles == 11 && out == 2 && pc == 4 && tos == 'c'\n\
+                  delete\nrcontinue\nassert cycles == 0 && out == 0 && pc == 0 && sp == 0 && !nz\n\
+                  assert mem[6] == 0\ncontinue\nassert cycles == 15 && out == 3\ninput\n";
+    let output = run_script(script);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Start of the history reached after 11 steps back."));
+    assert!(stdout.ends_with("Input: 3 bytes read, 0 remaining \"\".\n"));
+}
+
+#[test]
+fn session_and_replay() {
+    let dir = std::env::temp_dir().join(format!("rmdb-session-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (session, log) = (dir.join("print_input.session"), dir.join("steps.rmdb"));
+    let script = format!("copy misc/print_input.rm\ninput \"abc\"\nbreak op O if out > 0\n\
+                          record {log}\nstep 5\nrecord stop\nsave-session {session}\n\
+                          continue\nassert cycles == 7\n\
+                          delete\nload-session {session}\ninfo breakpoints\n\
+                          assert cycles == 5 && out == 1 && pc == 1 && sp == 0\ninput\n\
+                          delete\nreset\nreplay {log}\nassert cycles == 10 && out == 2 && pc == 1\n",
+                         log = log.display(), session = session.display());
+    let output = run_script(&script);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let recorded = std::fs::read_to_string(&log).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("Breakpoint 2 on op code 'O' (Out) if `out > 0`\n"));
+    assert!(stdout.contains("Input: 1 bytes read, 2 remaining \"bc\".\n"));
+    assert_eq!(recorded, "step 5\n");
+}
+
+#[test]
+fn session_keeps_a_custom_dialect() {
+    let dir = std::env::temp_dir().join(format!("rmdb-dialect-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let session = dir.join("custom.session");
+    let script = format!("copy misc/print_input.rm\ndialect iota,div-by-zero=zero\nsave-session {session}\n\
+                          dialect reustmann\nload-session {session}\ndialect\n",
+                         session = session.display());
+    let output = run_script(&script);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let saved = std::fs::read_to_string(&session).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(saved.contains("\ndialect: iota,div-by-zero=zero\n"), "{}", saved);
+    assert!(stdout.ends_with("Dialect: custom (div: quotient and remainder, div by zero: zero, \
+                              target search wraps: no, loop search wraps: no, \
+                              BRAN sets SP: no, push sets NZ: no).\n"), "{}", stdout);
+}
+
+#[test]
+fn asm_skips_whitespace() {
+    let output = run_script("copy misc/print_input.rm\nasm 1 P O\nassert mem[1] == 11 && mem[2] == 4 && mem[3] == 2\n");
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_script("copy misc/print_input.rm\nasm 1 Px\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "-:2: invalid mnemonic 'x'\n");
+}
@@ -0,0 +1,77 @@
+extern crate reustmann;
+
+use std::io::{empty, sink};
+
+use reustmann::{Dialect, Interpreter, Program, StepDetails};
+
+// execute the first instruction of `source` with `stack` from SP 5 upward
+fn step(source: &[u8], stack: &[u32], dialect: Dialect) -> StepDetails {
+    let mut interpreter = Interpreter::new(8, 8).unwrap();
+    interpreter.set_dialect(dialect);
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    for (i, &word) in stack.iter().enumerate() {
+        interpreter.poke(5 + i, word);
+    }
+    interpreter.set_sp(5);
+    interpreter.step_detailed(&mut empty(), &mut sink())
+}
+
+#[test]
+fn push() {
+    let details = step(b"0", &[], Dialect::reustmann());
+    assert_eq!((details.sp, details.next_sp), (5, 4));
+    assert_eq!(details.pushed, Some(0));
+    assert_eq!(details.popped, None);
+}
+
+#[test]
+fn pop() {
+    let details = step(b"p", &[9], Dialect::reustmann());
+    assert_eq!((details.sp, details.next_sp), (5, 6));
+    assert_eq!(details.pushed, None);
+    assert_eq!(details.popped, Some(9));
+}
+
+#[test]
+fn swap_neither_pushes_nor_pops() {
+    let details = step(b"S", &[1, 2], Dialect::reustmann());
+    assert_eq!(details.next_sp, 5);
+    assert_eq!(details.pushed, None);
+    assert_eq!(details.popped, None);
+}
+
+#[test]
+fn div_in_both_dialects() {
+    let details = step(b"/", &[2, 7], Dialect::reustmann());
+    assert_eq!(details.next_sp, 4);
+    assert_eq!(details.pushed, Some(3));
+    assert_eq!(details.popped, None);
+
+    let details = step(b"/", &[2, 7], Dialect::iota());
+    assert_eq!(details.next_sp, 5);
+    assert_eq!(details.pushed, None);
+    assert_eq!(details.popped, None);
+}
+
+#[test]
+fn taken_skip() {
+    let details = step(b"1", &[], Dialect::reustmann());
+    assert_eq!((details.pc, details.next_pc), (0, 2));
+    assert!(details.branch_taken);
+
+    let details = step(b"Z", &[], Dialect::reustmann());
+    assert_eq!(details.next_pc, 2);
+    assert!(details.branch_taken);
+
+    let details = step(b";", &[], Dialect::reustmann());
+    assert_eq!(details.next_pc, 1);
+    assert!(!details.branch_taken);
+}
+
+#[test]
+fn taken_branch() {
+    let details = step(b"B;T", &[], Dialect::iota());
+    assert_eq!((details.pc, details.next_pc), (0, 3));
+    assert!(details.branch_taken);
+    assert_eq!((details.pushed, details.popped), (None, None));
+}
@@ -0,0 +1,82 @@
+extern crate reustmann;
+
+use std::io::{empty, sink};
+
+use reustmann::instruction::op_codes::{DEC, INC};
+use reustmann::{CellAccess, Interpreter, MemoryEvent, MemoryEventKind, Program};
+
+fn interpreter(source: &[u8]) -> Interpreter {
+    let mut interpreter = Interpreter::new(source.len(), 8).unwrap();
+    interpreter.copy_program(&Program::from_iter(source.iter().cloned()));
+    interpreter
+}
+
+#[test]
+fn self_modifying_code() {
+    // the INC at 0 increments itself into a DEC through the SP
+    let mut interpreter = interpreter(b".;;;");
+    interpreter.enable_memory_watcher();
+
+    interpreter.step(&mut empty(), &mut sink());
+    assert_eq!(interpreter.drain_memory_events(), [MemoryEvent {
+        addr: 0,
+        pc: 0,
+        kind: MemoryEventKind::StackOverwroteCode { old: INC as u32, new: DEC as u32 },
+    }]);
+
+    for _ in 0..4 {
+        interpreter.step(&mut empty(), &mut sink());
+    }
+    assert_eq!(interpreter.drain_memory_events(), [
+        MemoryEvent { addr: 0, pc: 0, kind: MemoryEventKind::ModifiedCodeExecuted { op_code: DEC } },
+        MemoryEvent { addr: 0, pc: 0, kind: MemoryEventKind::StackOverwroteCode { old: DEC as u32, new: INC as u32 } },
+    ]);
+    assert!(interpreter.drain_memory_events().is_empty());
+}
+
+#[test]
+fn stack_overwrites_executed_code() {
+    // PUSH0 writes at 3, executed as a NOP afterward, then at 2, already executed
+    let mut interpreter = interpreter(b";0;;");
+    interpreter.enable_memory_watcher();
+    for _ in 0..6 {
+        interpreter.step(&mut empty(), &mut sink());
+    }
+    assert_eq!(interpreter.drain_memory_events(), [
+        MemoryEvent { addr: 3, pc: 3, kind: MemoryEventKind::ModifiedCodeExecuted { op_code: 0 } },
+        MemoryEvent { addr: 2, pc: 1, kind: MemoryEventKind::StackOverwroteCode { old: 0, new: 0 } },
+    ]);
+}
+
+#[test]
+fn cell_access() {
+    let mut interpreter = interpreter(b".;;;");
+    interpreter.enable_memory_watcher();
+    interpreter.step(&mut empty(), &mut sink());
+    interpreter.step(&mut empty(), &mut sink());
+
+    let watcher = interpreter.memory_watcher().unwrap();
+    assert_eq!(watcher.access(0), CellAccess { executed: true, read: true, written: true });
+    assert_eq!(watcher.access(1), CellAccess { executed: true, read: false, written: false });
+    assert_eq!(watcher.access(2), CellAccess::default());
+    assert!(watcher.access(0).is_code() && watcher.access(0).is_data());
+    assert!(!watcher.access(1).is_data());
+}
+
+#[test]
+fn disabled_watcher() {
+    let mut interpreter = interpreter(b".;;;");
+    interpreter.step(&mut empty(), &mut sink());
+    assert!(interpreter.memory_watcher().is_none());
+    assert!(interpreter.drain_memory_events().is_empty());
+}
+
+#[test]
+fn copy_program_clears_the_watcher() {
+    let mut interpreter = interpreter(b".;;;");
+    interpreter.enable_memory_watcher();
+    interpreter.step(&mut empty(), &mut sink());
+    interpreter.copy_program(&Program::from_iter(b".;;;".iter().cloned()));
+    assert!(interpreter.drain_memory_events().is_empty());
+    assert_eq!(interpreter.memory_watcher().unwrap().access(0), CellAccess::default());
+}